@@ -0,0 +1,25 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use relative_path::RelativePathBuf;
+use std::hint::black_box;
+
+const COUNTS: &[usize] = &[1_000, 10_000, 100_000];
+
+fn common_base(c: &mut Criterion) {
+    let mut group = c.benchmark_group("common_base");
+
+    for &count in COUNTS {
+        let paths = (0..count)
+            .map(|n| RelativePathBuf::from(format!("foo/bar/baz/{}/file.txt", n)))
+            .collect::<Vec<_>>();
+
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &paths, |b, paths| {
+            b.iter(|| relative_path::common_base(black_box(paths)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, common_base);
+criterion_main!(benches);
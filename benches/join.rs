@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use relative_path::RelativePath;
+use std::hint::black_box;
+
+const BASES: &[&str] = &[
+    "",
+    "foo",
+    "foo/bar/baz/biz",
+    "src/components/widgets/buttons/primary/styles/themes/dark",
+];
+
+const PATHS: &[&str] = &[
+    "a/b/c",
+    "assets/images/icons/navigation/arrows/left-arrow.svg",
+];
+
+fn join(c: &mut Criterion) {
+    let mut group = c.benchmark_group("join");
+
+    for base in BASES {
+        let base = RelativePath::new(base);
+
+        for path in PATHS {
+            let id = format!("{}+{}", base, path);
+
+            group.bench_with_input(BenchmarkId::new("join", &id), base, |b, base| {
+                b.iter(|| black_box(base).join(black_box(path)))
+            });
+
+            group.bench_with_input(BenchmarkId::new("to_owned_push", &id), base, |b, base| {
+                b.iter(|| {
+                    let mut out = black_box(base).to_owned();
+                    out.push(black_box(path));
+                    out
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, join);
+criterion_main!(benches);
@@ -51,25 +51,49 @@
 //! # Serde Support
 //!
 //! This library includes serde support that can be enabled with the `serde` feature.
+//!
+//! Helpers for alternative serialized forms are available in the [`serde`] module.
+//!
+//! [`serde`]: serde/index.html
+//!
+//! # SmolStr Support
+//!
+//! Enabling the `smol_str` feature adds [`smol_components`], which yields components as
+//! [`SmolStr`] values that store short strings inline.
+//!
+//! [`smol_components`]: struct.RelativePath.html#method.smol_components
+//! [`SmolStr`]: https://docs.rs/smol_str
+//!
+//! # SmallVec Support
+//!
+//! Enabling the `smallvec` feature adds [`components_smallvec`], which collects components into a
+//! [`SmallVec`] that avoids allocating for shallow paths.
+//!
+//! [`components_smallvec`]: struct.RelativePath.html#method.components_smallvec
+//! [`SmallVec`]: https://docs.rs/smallvec
 
 use std::borrow::{Borrow, Cow};
 use std::cmp;
+use std::collections::HashSet;
+use std::convert::Infallible;
 use std::error;
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter;
 use std::mem;
 use std::ops::{self, Deref};
 use std::path;
+use std::rc::Rc;
 use std::str;
-
-#[cfg(feature = "serde")]
-extern crate serde;
+use std::sync::Arc;
 
 const STEM_SEP: char = '.';
 const CURRENT_STR: &str = ".";
 const PARENT_STR: &str = "..";
 
 const SEP: char = '/';
+/// The maximum length in bytes of a path produced by [`RelativePath::join_checked`].
+const MAX_JOIN_LEN: usize = 4096;
 
 fn split_file_at_dot(input: &str) -> (Option<&str>, Option<&str>) {
     if input == PARENT_STR {
@@ -91,16 +115,26 @@ fn split_file_at_dot(input: &str) -> (Option<&str>, Option<&str>) {
 // Iterate through `iter` while it matches `prefix`; return `None` if `prefix`
 // is not a prefix of `iter`, otherwise return `Some(iter_after_prefix)` giving
 // `iter` after having exhausted `prefix`.
-fn iter_after<A, I, J>(mut iter: I, mut prefix: J) -> Option<I>
+fn iter_after<A, I, J>(iter: I, prefix: J) -> Option<I>
 where
     I: Iterator<Item = A> + Clone,
     J: Iterator<Item = A>,
     A: PartialEq,
+{
+    iter_after_by(iter, prefix, |x, y| x == y)
+}
+
+// Like `iter_after`, but compares items using `eq`.
+fn iter_after_by<A, B, I, J, F>(mut iter: I, mut prefix: J, eq: F) -> Option<I>
+where
+    I: Iterator<Item = A> + Clone,
+    J: Iterator<Item = B>,
+    F: Fn(&A, &B) -> bool,
 {
     loop {
         let mut iter_next = iter.clone();
         match (iter_next.next(), prefix.next()) {
-            (Some(ref x), Some(ref y)) if x == y => (),
+            (Some(ref x), Some(ref y)) if eq(x, y) => (),
             (Some(_), Some(_)) => return None,
             (Some(_), None) => return Some(iter),
             (None, None) => return Some(iter),
@@ -169,6 +203,76 @@ where
     }
 }
 
+/// Percent-decode a single component.
+fn percent_decode(component: &str) -> Result<Cow<'_, str>, DecodeError> {
+    fn hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    if !component.contains('%') {
+        return Ok(Cow::Borrowed(component));
+    }
+
+    let mut bytes = component.bytes();
+    let mut out = Vec::with_capacity(component.len());
+
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            out.push(b);
+            continue;
+        }
+
+        let hi = bytes.next().and_then(hex);
+        let lo = bytes.next().and_then(hex);
+
+        let b = match (hi, lo) {
+            (Some(hi), Some(lo)) => hi << 4 | lo,
+            _ => return Err(DecodeErrorKind::InvalidEscape.into()),
+        };
+
+        if b == SEP as u8 {
+            return Err(DecodeErrorKind::EncodedSeparator.into());
+        }
+
+        out.push(b);
+    }
+
+    match String::from_utf8(out) {
+        Ok(s) => Ok(Cow::Owned(s)),
+        Err(_) => Err(DecodeErrorKind::NonUtf8.into()),
+    }
+}
+
+/// Extend `base` with the given components, resolving them logically.
+///
+/// A '..' pops the last normal component of `base`, or is pushed as-is if there is none. Nothing
+/// can be popped above a filesystem root or prefix.
+fn logical_extend<'a, C>(base: &mut path::PathBuf, components: C)
+where
+    C: IntoIterator<Item = Component<'a>>,
+{
+    use self::Component::*;
+
+    for c in components {
+        match c {
+            CurDir => (),
+            ParentDir => match base.components().next_back() {
+                Some(path::Component::Normal(_)) => {
+                    base.pop();
+                }
+                Some(path::Component::RootDir) | Some(path::Component::Prefix(_)) => (),
+                _ => base.push(PARENT_STR),
+            },
+            Normal(name) => base.push(name),
+        }
+    }
+}
+
 /// Iterator over all the components in a relative path.
 #[derive(Clone)]
 pub struct Components<'a> {
@@ -187,7 +291,7 @@ impl<'a> Iterator for Components<'a> {
                 self.source = rest.trim_start_matches(SEP);
                 slice
             }
-            None => mem::replace(&mut self.source, ""),
+            None => mem::take(&mut self.source),
         };
 
         match slice {
@@ -197,6 +301,11 @@ impl<'a> Iterator for Components<'a> {
             slice => Some(Component::Normal(slice)),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every component but the last is followed by at least one separator.
+        (0, Some(self.source.len().div_ceil(2)))
+    }
 }
 
 impl<'a> DoubleEndedIterator for Components<'a> {
@@ -209,7 +318,7 @@ impl<'a> DoubleEndedIterator for Components<'a> {
                 self.source = rest.trim_end_matches(SEP);
                 slice
             }
-            None => mem::replace(&mut self.source, ""),
+            None => mem::take(&mut self.source),
         };
 
         match slice {
@@ -228,6 +337,9 @@ impl<'a> Components<'a> {
 
     /// Extracts a slice corresponding to the portion of the path remaining for iteration.
     ///
+    /// This reflects components consumed from both the front and the back, so the returned path
+    /// is always the unconsumed middle of the original path.
+    ///
     /// # Examples
     ///
     /// ```
@@ -238,6 +350,12 @@ impl<'a> Components<'a> {
     /// components.next();
     ///
     /// assert_eq!(RelativePath::new("bar.txt"), components.as_relative_path());
+    ///
+    /// let mut components = RelativePath::new("a//b/c//d/").components();
+    /// components.next();
+    /// components.next_back();
+    ///
+    /// assert_eq!("b/c", components.as_relative_path().as_str());
     /// ```
     pub fn as_relative_path(&self) -> &'a RelativePath {
         RelativePath::new(self.source)
@@ -270,6 +388,10 @@ impl<'a> Iterator for Iter<'a> {
     fn next(&mut self) -> Option<&'a str> {
         self.inner.next().map(Component::as_str)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
 }
 
 impl<'a> DoubleEndedIterator for Iter<'a> {
@@ -278,6 +400,83 @@ impl<'a> DoubleEndedIterator for Iter<'a> {
     }
 }
 
+/// A matcher for a single path component, used with [`RelativePath::matches_pattern`].
+///
+/// This is implemented for string slices, which match a component exactly, and for closures
+/// which take a component and return whether it matches.
+///
+/// [`RelativePath::matches_pattern`]: struct.RelativePath.html#method.matches_pattern
+pub trait SegmentMatcher {
+    /// Test if the given path component matches.
+    fn matches(&self, segment: &str) -> bool;
+
+    /// Test if this matcher matches any number of consecutive components, like `**` in a glob.
+    ///
+    /// Each component consumed must still satisfy [`matches`]. Defaults to `false`.
+    ///
+    /// [`matches`]: #tymethod.matches
+    fn matches_any_depth(&self) -> bool {
+        false
+    }
+}
+
+/// A [`SegmentMatcher`] which matches zero or more path components, like `**` in a glob.
+///
+/// [`SegmentMatcher`]: SegmentMatcher
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnyDepth;
+
+impl SegmentMatcher for AnyDepth {
+    fn matches(&self, _: &str) -> bool {
+        true
+    }
+
+    fn matches_any_depth(&self) -> bool {
+        true
+    }
+}
+
+impl SegmentMatcher for &str {
+    fn matches(&self, segment: &str) -> bool {
+        *self == segment
+    }
+}
+
+impl<F> SegmentMatcher for F
+where
+    F: Fn(&str) -> bool,
+{
+    fn matches(&self, segment: &str) -> bool {
+        self(segment)
+    }
+}
+
+/// Test if `segments` are matched in their entirety by the sequence of `matchers`.
+fn match_segments(matchers: &[&dyn SegmentMatcher], mut segments: Iter<'_>) -> bool {
+    let (first, rest) = match matchers.split_first() {
+        Some(split) => split,
+        None => return segments.next().is_none(),
+    };
+
+    if first.matches_any_depth() {
+        loop {
+            if match_segments(rest, segments.clone()) {
+                return true;
+            }
+
+            match segments.next() {
+                Some(segment) if first.matches(segment) => (),
+                _ => return false,
+            }
+        }
+    }
+
+    match segments.next() {
+        Some(segment) if first.matches(segment) => match_segments(rest, segments),
+        _ => false,
+    }
+}
+
 #[derive(Copy, Debug, Clone, PartialEq, Eq)]
 pub enum FromPathErrorKind {
     /// Non-relative component in path.
@@ -300,20 +499,253 @@ impl From<FromPathErrorKind> for FromPathError {
     }
 }
 
+impl FromPathError {
+    fn message(&self) -> &'static str {
+        use self::FromPathErrorKind::*;
+
+        match self.kind {
+            NonRelative => "path contains non-relative component",
+            NonUtf8 => "path contains non-utf8 component",
+            BadSeparator => "path contains platform-specific path separator",
+        }
+    }
+}
+
 impl fmt::Display for FromPathError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        <Self as error::Error>::description(self).fmt(fmt)
+        self.message().fmt(fmt)
     }
 }
 
 impl error::Error for FromPathError {
     fn description(&self) -> &str {
-        use self::FromPathErrorKind::*;
+        self.message()
+    }
+}
+
+/// How a [`RelativePathJoiner`] handles paths which start with a separator.
+///
+/// [`RelativePathJoiner`]: struct.RelativePathJoiner.html
+#[derive(Copy, Debug, Clone, Default, PartialEq, Eq)]
+pub enum AbsolutePolicy {
+    /// Replace everything joined so far with the path.
+    Reset,
+    /// Ignore the leading separator and append the path, like [`RelativePathBuf::push`]. This is
+    /// the default.
+    ///
+    /// [`RelativePathBuf::push`]: struct.RelativePathBuf.html#method.push
+    #[default]
+    Ignore,
+    /// Fail with [`JoinErrorKind::Absolute`] when building.
+    ///
+    /// [`JoinErrorKind::Absolute`]: enum.JoinErrorKind.html#variant.Absolute
+    Error,
+}
+
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum JoinErrorKind {
+    /// Tried to join a path which starts with a separator.
+    Absolute,
+    /// Tried to join a path with a `..` component which escapes the path it is joined onto.
+    Escapes,
+    /// The joined path would be too long.
+    TooLong,
+}
+
+/// An error raised when joining paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinError {
+    kind: JoinErrorKind,
+}
+
+impl From<JoinErrorKind> for JoinError {
+    fn from(value: JoinErrorKind) -> Self {
+        Self { kind: value }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::JoinErrorKind::*;
 
         match self.kind {
-            NonRelative => "path contains non-relative component",
-            NonUtf8 => "path contains non-utf8 component",
-            BadSeparator => "path contains platform-specific path separator",
+            Absolute => "joined path starts with a separator",
+            Escapes => "joined path escapes the base path",
+            TooLong => "joined path is too long",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl error::Error for JoinError {}
+
+/// How [`RelativePath::to_path_with`] handles `..` components.
+///
+/// [`RelativePath::to_path_with`]: struct.RelativePath.html#method.to_path_with
+#[derive(Copy, Debug, Clone, Default, PartialEq, Eq)]
+pub enum ParentDirPolicy {
+    /// Keep `..` as a literal component, like [`RelativePath::to_path`]. This is the default.
+    ///
+    /// [`RelativePath::to_path`]: struct.RelativePath.html#method.to_path
+    #[default]
+    Literal,
+    /// Resolve `..` logically by removing the last normal component of the path built so far,
+    /// which might belong to the base. Nothing is removed above a filesystem root or prefix.
+    Resolve,
+    /// Fail with a [`ParentDirError`].
+    ///
+    /// [`ParentDirError`]: struct.ParentDirError.html
+    Reject,
+}
+
+/// An error returned from [`RelativePath::to_path_with`] if the path contains a `..` component
+/// while using [`ParentDirPolicy::Reject`].
+///
+/// [`RelativePath::to_path_with`]: struct.RelativePath.html#method.to_path_with
+/// [`ParentDirPolicy::Reject`]: enum.ParentDirPolicy.html#variant.Reject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParentDirError(());
+
+impl fmt::Display for ParentDirError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        "path contains a parent directory component".fmt(fmt)
+    }
+}
+
+impl error::Error for ParentDirError {}
+
+/// An error returned from [`RelativePath::normalize_max_up`] if the normalized path would start
+/// with too many `..` components.
+///
+/// [`RelativePath::normalize_max_up`]: struct.RelativePath.html#method.normalize_max_up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyUpError(());
+
+impl fmt::Display for TooManyUpError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        "normalized path has too many parent directory components".fmt(fmt)
+    }
+}
+
+impl error::Error for TooManyUpError {}
+
+/// The kind of error raised by [`RelativePath::decoded_components`].
+///
+/// [`RelativePath::decoded_components`]: struct.RelativePath.html#method.decoded_components
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    /// A `%` was not followed by two hexadecimal digits.
+    InvalidEscape,
+    /// A component contained an encoded separator (`%2F`).
+    EncodedSeparator,
+    /// A decoded component is not valid UTF-8.
+    NonUtf8,
+}
+
+/// An error raised when percent-decoding the components of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    kind: DecodeErrorKind,
+}
+
+impl From<DecodeErrorKind> for DecodeError {
+    fn from(value: DecodeErrorKind) -> Self {
+        Self { kind: value }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::DecodeErrorKind::*;
+
+        match self.kind {
+            InvalidEscape => "invalid percent escape in component",
+            EncodedSeparator => "encoded separator in component",
+            NonUtf8 => "decoded component is not valid UTF-8",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// A builder which joins several paths, with an explicit [`AbsolutePolicy`] for paths which start
+/// with a separator.
+///
+/// [`AbsolutePolicy`]: AbsolutePolicy
+///
+/// # Examples
+///
+/// ```rust
+/// use relative_path::{AbsolutePolicy, JoinErrorKind, RelativePathJoiner};
+///
+/// let path = RelativePathJoiner::new().join("foo").join("/bar").build();
+/// assert_eq!(Ok("foo/bar".into()), path);
+///
+/// let path = RelativePathJoiner::new()
+///     .absolute_policy(AbsolutePolicy::Reset)
+///     .join("foo")
+///     .join("/bar")
+///     .build();
+/// assert_eq!(Ok("/bar".into()), path);
+///
+/// let path = RelativePathJoiner::new()
+///     .absolute_policy(AbsolutePolicy::Error)
+///     .join("foo")
+///     .join("/bar")
+///     .build();
+/// assert_eq!(Err(JoinErrorKind::Absolute.into()), path);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RelativePathJoiner {
+    buf: RelativePathBuf,
+    policy: AbsolutePolicy,
+    error: Option<JoinError>,
+}
+
+impl RelativePathJoiner {
+    /// Create a new joiner, starting from an empty path.
+    pub fn new() -> RelativePathJoiner {
+        RelativePathJoiner::default()
+    }
+
+    /// Set the policy used for paths joined after this call.
+    pub fn absolute_policy(mut self, policy: AbsolutePolicy) -> RelativePathJoiner {
+        self.policy = policy;
+        self
+    }
+
+    /// Join `path` onto the path built so far.
+    pub fn join<P: AsRef<RelativePath>>(mut self, path: P) -> RelativePathJoiner {
+        let path = path.as_ref();
+
+        if path.starts_with_sep() {
+            match self.policy {
+                AbsolutePolicy::Reset => {
+                    self.buf = path.to_relative_path_buf();
+                    return self;
+                }
+                AbsolutePolicy::Ignore => (),
+                AbsolutePolicy::Error => {
+                    self.error.get_or_insert(JoinErrorKind::Absolute.into());
+                    return self;
+                }
+            }
+        }
+
+        self.buf.push(path);
+        self
+    }
+
+    /// Build the joined path.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered while joining, if any.
+    pub fn build(self) -> Result<RelativePathBuf, JoinError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.buf),
         }
     }
 }
@@ -367,75 +799,288 @@ impl RelativePathBuf {
         Ok(buffer)
     }
 
-    /// Extends `self` with `path`.
+    /// Try to convert a [`Path`] to a `RelativePathBuf`, preserving all of
+    /// its `.` and `..` components.
+    ///
+    /// Unlike [`from_path`], which drops `.` components, this maps every
+    /// component of the path as it is written. Redundant separators are not
+    /// preserved.
+    ///
+    /// [`Path`]: std::path::Path
+    /// [`from_path`]: #method.from_path
     ///
-    /// If `path` is absolute, it replaces the current path.
+    /// # Errors
+    ///
+    /// Errors with [`FromPathErrorKind::NonRelative`] if the path has a root
+    /// or a prefix, and with [`FromPathErrorKind::NonUtf8`] if it isn't valid
+    /// UTF-8.
+    ///
+    /// [`FromPathErrorKind::NonRelative`]: enum.FromPathErrorKind.html#variant.NonRelative
+    /// [`FromPathErrorKind::NonUtf8`]: enum.FromPathErrorKind.html#variant.NonUtf8
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use relative_path::{RelativePathBuf, RelativePath};
+    /// use relative_path::{FromPathErrorKind, RelativePathBuf};
+    /// use std::path::Path;
     ///
-    /// let mut path = RelativePathBuf::new();
-    /// path.push("foo");
-    /// path.push("bar");
+    /// let path = RelativePathBuf::from_relative_std_path(Path::new("./foo/./bar/../baz"))?;
+    /// assert_eq!("./foo/./bar/../baz", path.as_str());
     ///
-    /// assert_eq!("foo/bar", path);
+    /// assert_eq!(
+    ///     Err(FromPathErrorKind::NonRelative.into()),
+    ///     RelativePathBuf::from_relative_std_path(Path::new("/foo"))
+    /// );
+    /// # Ok::<_, relative_path::FromPathError>(())
     /// ```
-    pub fn push<P: AsRef<RelativePath>>(&mut self, path: P) {
-        let other = path.as_ref();
+    pub fn from_relative_std_path<P: AsRef<path::Path>>(
+        path: P,
+    ) -> Result<RelativePathBuf, FromPathError> {
+        use std::path::Component::*;
 
-        let other = if other.starts_with_sep() {
-            &other.inner[1..]
-        } else {
-            &other.inner[..]
-        };
+        let path = path.as_ref();
 
-        if !self.inner.is_empty() && !self.ends_with_sep() {
-            self.inner.push(SEP);
+        if path.components().any(|c| matches!(c, Prefix(_) | RootDir)) {
+            return Err(FromPathErrorKind::NonRelative.into());
         }
 
-        self.inner.push_str(other)
+        let s = path.to_str().ok_or(FromPathErrorKind::NonUtf8)?;
+        let mut buffer = RelativePathBuf::new();
+
+        for c in s.split(path::is_separator).filter(|c| !c.is_empty()) {
+            buffer.push(c);
+        }
+
+        Ok(buffer)
     }
 
-    /// Updates [`self.file_name`] to `file_name`.
+    /// Convert a [`Path`] to a `RelativePathBuf`, ignoring any root or prefix
+    /// components.
     ///
-    /// If [`self.file_name`] was [`None`], this is equivalent to pushing
-    /// `file_name`.
-    ///
-    /// Otherwise it is equivalent to calling [`pop`] and then pushing
-    /// `file_name`. The new path will be a sibling of the original path.
-    /// (That is, it will have the same parent.)
+    /// Unlike the strict [`from_path`], this never fails. Components which
+    /// aren't valid UTF-8 are converted lossily, and platform-specific
+    /// separators are replaced with `/`.
     ///
-    /// [`self.file_name`]: struct.RelativePathBuf.html#method.file_name
-    /// [`pop`]: struct.RelativePathBuf.html#method.pop
-    /// [`None`]: std::option::Option
+    /// [`Path`]: std::path::Path
+    /// [`from_path`]: #method.from_path
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```rust
     /// use relative_path::RelativePathBuf;
+    /// use std::path::Path;
     ///
-    /// let mut buf = RelativePathBuf::from("");
-    /// assert!(buf.file_name() == None);
-    /// buf.set_file_name("bar");
-    /// assert_eq!(RelativePathBuf::from("bar"), buf);
-    ///
-    /// assert!(buf.file_name().is_some());
-    /// buf.set_file_name("baz.txt");
-    /// assert_eq!(RelativePathBuf::from("baz.txt"), buf);
+    /// assert_eq!("foo/../bar", RelativePathBuf::from_path_lossy(Path::new("/foo/../bar")));
     ///
-    /// buf.push("bar");
-    /// assert!(buf.file_name().is_some());
-    /// buf.set_file_name("bar.txt");
-    /// assert_eq!(RelativePathBuf::from("baz.txt/bar.txt"), buf);
+    /// if cfg!(windows) {
+    ///     assert_eq!("foo/bar", RelativePathBuf::from_path_lossy(Path::new("C:\\foo\\bar")));
+    /// }
     /// ```
-    pub fn set_file_name<S: AsRef<str>>(&mut self, file_name: S) {
-        let file_name = file_name.as_ref();
+    pub fn from_path_lossy<P: AsRef<path::Path>>(path: P) -> RelativePathBuf {
+        use std::path::Component::*;
 
-        if self.file_name().is_some() && !self.pop() {
-            self.inner = file_name.to_string();
-            return;
+        let mut buffer = RelativePathBuf::new();
+
+        for c in path.as_ref().components() {
+            match c {
+                Prefix(_) | RootDir => continue,
+                c => buffer.push(c.as_os_str().to_string_lossy().as_ref()),
+            }
+        }
+
+        buffer
+    }
+
+    /// Parse a path which uses `sep` as a separator instead of `/`.
+    ///
+    /// Empty segments are skipped, like they are by [`components`]. This is the inverse of
+    /// [`join_with_separator`].
+    ///
+    /// [`components`]: struct.RelativePath.html#method.components
+    /// [`join_with_separator`]: #method.join_with_separator
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// assert_eq!("a/b/c", RelativePathBuf::from_separated("a\\b\\\\c\\", '\\'));
+    ///
+    /// let path = RelativePathBuf::from_separated("a.b.c", '.');
+    /// assert_eq!("a.b.c", RelativePathBuf::join_with_separator(path.iter(), '.'));
+    /// ```
+    pub fn from_separated(s: &str, sep: char) -> RelativePathBuf {
+        let mut buf = RelativePathBuf::new();
+
+        for part in s.split(sep).filter(|part| !part.is_empty()) {
+            buf.push(part);
+        }
+
+        buf
+    }
+
+    /// Join `parts` into a string, using `sep` as a separator instead of `/`.
+    ///
+    /// This is useful to build the stored form of a path for external formats which use a
+    /// different separator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let path = RelativePath::new("foo//bar/baz");
+    ///
+    /// assert_eq!("foo:bar:baz", RelativePathBuf::join_with_separator(path.iter(), ':'));
+    /// assert_eq!("", RelativePathBuf::join_with_separator(Vec::<String>::new(), ':'));
+    /// ```
+    pub fn join_with_separator<I, S>(parts: I, sep: char) -> String
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut out = String::new();
+
+        for (n, part) in parts.into_iter().enumerate() {
+            if n > 0 {
+                out.push(sep);
+            }
+
+            out.push_str(part.as_ref());
+        }
+
+        out
+    }
+
+    /// Extends `self` with `path`.
+    ///
+    /// A leading separator in `path` is ignored, so it is always appended to the current path. See
+    /// [`RelativePathJoiner`] for other ways of handling this.
+    ///
+    /// [`RelativePathJoiner`]: struct.RelativePathJoiner.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{RelativePathBuf, RelativePath};
+    ///
+    /// let mut path = RelativePathBuf::new();
+    /// path.push("foo");
+    /// path.push("bar");
+    ///
+    /// assert_eq!("foo/bar", path);
+    /// ```
+    pub fn push<P: AsRef<RelativePath>>(&mut self, path: P) {
+        let other = path.as_ref().trim_sep_once();
+
+        if !self.inner.is_empty() && !self.has_trailing_separator() {
+            self.inner.push(SEP);
+        }
+
+        self.inner.push_str(other)
+    }
+
+    /// Inserts `component` at the front of `self`, in place.
+    ///
+    /// This is the front-inserting complement to [`push`]. A separator is
+    /// inserted between `component` and the existing path unless one is
+    /// already present.
+    ///
+    /// [`push`]: #method.push
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// let mut path = RelativePathBuf::from("b/c");
+    /// path.prepend("a");
+    /// assert_eq!("a/b/c", path);
+    ///
+    /// let mut path = RelativePathBuf::new();
+    /// path.prepend("a");
+    /// assert_eq!("a", path);
+    /// ```
+    pub fn prepend(&mut self, component: &str) {
+        if component.is_empty() {
+            return;
+        }
+
+        if !self.inner.is_empty() && !self.starts_with_sep() && !component.ends_with(SEP) {
+            self.inner.insert(0, SEP);
+        }
+
+        self.inner.insert_str(0, component);
+    }
+
+    /// Extends `self` with each path in `parts`.
+    ///
+    /// This is equivalent to calling [`push`] for each part, but reserves the capacity needed up
+    /// front.
+    ///
+    /// [`push`]: #method.push
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// let mut path = RelativePathBuf::from("foo");
+    /// path.push_all(&["bar", "baz"]);
+    ///
+    /// assert_eq!("foo/bar/baz", path);
+    /// ```
+    pub fn push_all<S: AsRef<RelativePath>>(&mut self, parts: &[S]) {
+        let additional = parts
+            .iter()
+            .map(|p| p.as_ref().inner.len() + 1)
+            .sum::<usize>();
+
+        self.inner.reserve(additional);
+
+        for part in parts {
+            self.push(part);
+        }
+    }
+
+    /// Updates [`self.file_name`] to `file_name`.
+    ///
+    /// If [`self.file_name`] was [`None`], this is equivalent to pushing
+    /// `file_name`.
+    ///
+    /// Otherwise it is equivalent to calling [`pop`] and then pushing
+    /// `file_name`. The new path will be a sibling of the original path.
+    /// (That is, it will have the same parent.)
+    ///
+    /// [`self.file_name`]: struct.RelativePathBuf.html#method.file_name
+    /// [`pop`]: struct.RelativePathBuf.html#method.pop
+    /// [`None`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// let mut buf = RelativePathBuf::from("");
+    /// assert!(buf.file_name() == None);
+    /// buf.set_file_name("bar");
+    /// assert_eq!(RelativePathBuf::from("bar"), buf);
+    ///
+    /// assert!(buf.file_name().is_some());
+    /// buf.set_file_name("baz.txt");
+    /// assert_eq!(RelativePathBuf::from("baz.txt"), buf);
+    ///
+    /// buf.push("bar");
+    /// assert!(buf.file_name().is_some());
+    /// buf.set_file_name("bar.txt");
+    /// assert_eq!(RelativePathBuf::from("baz.txt/bar.txt"), buf);
+    /// ```
+    pub fn set_file_name<S: AsRef<str>>(&mut self, file_name: S) {
+        let file_name = file_name.as_ref();
+
+        if self.file_name().is_some() && !self.pop() {
+            self.inner = file_name.to_string();
+            return;
         }
 
         self.push(file_name);
@@ -471,23 +1116,21 @@ impl RelativePathBuf {
     /// assert_eq!(RelativePath::new("feel.nothing"), p);
     /// ```
     pub fn set_extension<S: AsRef<str>>(&mut self, extension: S) -> bool {
-        if self.file_name().is_none() {
-            return false;
-        }
-
-        let mut stem = match self.file_stem() {
-            Some(stem) => stem.to_string(),
-            None => String::new(),
+        let end = match self.file_stem() {
+            Some(stem) => self.offset_of(stem) + stem.len(),
+            None => return false,
         };
 
+        self.inner.truncate(end);
+
         let extension = extension.as_ref();
 
         if !extension.is_empty() {
-            stem.push(STEM_SEP);
-            stem += extension;
+            self.inner.reserve(extension.len() + 1);
+            self.inner.push(STEM_SEP);
+            self.inner.push_str(extension);
         }
 
-        self.set_file_name(&stem);
         true
     }
 
@@ -511,7 +1154,7 @@ impl RelativePathBuf {
     /// assert_eq!(RelativePath::new(""), p);
     /// ```
     pub fn pop(&mut self) -> bool {
-        match self.parent().map(|p| p.as_u8_slice().len()) {
+        match self.parent().map(|p| p.as_bytes().len()) {
             Some(len) => {
                 self.inner.truncate(len);
                 true
@@ -520,6 +1163,159 @@ impl RelativePathBuf {
         }
     }
 
+    /// Truncates `self` by calling [`pop`] up to `n` times.
+    ///
+    /// Returns the number of components actually removed, which is less than `n` if the path ran
+    /// out of components.
+    ///
+    /// [`pop`]: #method.pop
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let mut p = RelativePathBuf::from("a/b/c/d");
+    ///
+    /// assert_eq!(2, p.pop_n(2));
+    /// assert_eq!(RelativePath::new("a/b"), p);
+    /// assert_eq!(2, p.pop_n(5));
+    /// assert_eq!(RelativePath::new(""), p);
+    /// ```
+    pub fn pop_n(&mut self, n: usize) -> usize {
+        let mut popped = 0;
+
+        while popped < n && self.pop() {
+            popped += 1;
+        }
+
+        popped
+    }
+
+    /// Truncates `self` to at most `max` bytes by removing trailing components.
+    ///
+    /// The path is only ever cut at a component boundary, so no component is left partially
+    /// truncated. Trailing separators are removed first if that alone makes the path fit. Returns
+    /// `true` if anything was removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let mut p = RelativePathBuf::from("foo/bar/baz");
+    ///
+    /// assert!(!p.truncate_to_bytes(11));
+    /// assert_eq!(RelativePath::new("foo/bar/baz"), p);
+    /// assert!(p.truncate_to_bytes(10));
+    /// assert_eq!(RelativePath::new("foo/bar"), p);
+    /// assert!(p.truncate_to_bytes(2));
+    /// assert_eq!(RelativePath::new(""), p);
+    ///
+    /// let mut p = RelativePathBuf::from("foo/bar/");
+    /// assert!(p.truncate_to_bytes(7));
+    /// assert_eq!("foo/bar", p.as_str());
+    /// ```
+    pub fn truncate_to_bytes(&mut self, max: usize) -> bool {
+        if self.inner.len() <= max {
+            return false;
+        }
+
+        let trimmed = self.inner.trim_end_matches(SEP).len();
+
+        if trimmed <= max {
+            self.inner.truncate(trimmed);
+            return true;
+        }
+
+        let mut truncated = false;
+
+        while self.inner.len() > max && self.pop() {
+            truncated = true;
+        }
+
+        truncated
+    }
+
+    /// Replaces the contents of `self` with the given components, reusing the existing allocation.
+    ///
+    /// This is equivalent to clearing the path and calling [`push`] for each component.
+    ///
+    /// [`push`]: #method.push
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// let mut path = RelativePathBuf::from("foo/bar/baz");
+    /// let upper = path.iter().map(str::to_uppercase).collect::<Vec<_>>();
+    ///
+    /// path.set_components(upper);
+    /// assert_eq!("FOO/BAR/BAZ", path);
+    ///
+    /// path.set_components(Vec::<&str>::new());
+    /// assert_eq!("", path);
+    /// ```
+    pub fn set_components<I, S>(&mut self, components: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.inner.clear();
+
+        for c in components {
+            self.push(c.as_ref());
+        }
+    }
+
+    /// Converts this `RelativePathBuf` into a [boxed][`Box`] [`RelativePath`], dropping any excess
+    /// capacity.
+    ///
+    /// [`Box`]: std::boxed::Box
+    /// [`RelativePath`]: RelativePath
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let mut path = RelativePathBuf::new();
+    /// path.push("foo/bar");
+    ///
+    /// let boxed: Box<RelativePath> = path.into_boxed_relative_path();
+    /// assert_eq!("foo/bar", boxed.as_str());
+    /// ```
+    pub fn into_boxed_relative_path(self) -> Box<RelativePath> {
+        let raw = Box::into_raw(self.inner.into_boxed_str()) as *mut RelativePath;
+        unsafe { Box::from_raw(raw) }
+    }
+
+    /// [Normalizes] `self` in place, returning `true` if it was modified.
+    ///
+    /// [Normalizes]: struct.RelativePath.html#method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePathBuf;
+    ///
+    /// let mut path = RelativePathBuf::from("foo/./bar/../baz/");
+    /// assert!(path.normalize_in_place());
+    /// assert_eq!("foo/baz", path.as_str());
+    ///
+    /// assert!(!path.normalize_in_place());
+    /// assert_eq!("foo/baz", path.as_str());
+    /// ```
+    pub fn normalize_in_place(&mut self) -> bool {
+        if self.is_normalized() {
+            return false;
+        }
+
+        *self = self.normalize();
+        true
+    }
+
     /// Coerce to a [`RelativePath`] slice.
     ///
     /// [`RelativePath`]: RelativePath
@@ -580,6 +1376,27 @@ impl From<String> for RelativePathBuf {
     }
 }
 
+/// Parse a string as a `RelativePathBuf`.
+///
+/// Any string is a valid relative path, so this never fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use relative_path::RelativePathBuf;
+///
+/// let path = "foo/bar".parse::<RelativePathBuf>()?;
+/// assert_eq!("foo/bar", path);
+/// # Ok::<_, std::convert::Infallible>(())
+/// ```
+impl str::FromStr for RelativePathBuf {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(RelativePathBuf::from(s))
+    }
+}
+
 impl ops::Deref for RelativePathBuf {
     type Target = RelativePath;
 
@@ -598,7 +1415,7 @@ impl cmp::Eq for RelativePathBuf {}
 
 impl cmp::PartialOrd for RelativePathBuf {
     fn partial_cmp(&self, other: &RelativePathBuf) -> Option<cmp::Ordering> {
-        self.components().partial_cmp(other.components())
+        Some(self.cmp(other))
     }
 }
 
@@ -614,6 +1431,58 @@ impl Hash for RelativePathBuf {
     }
 }
 
+impl From<RelativePathBuf> for Box<RelativePath> {
+    fn from(path: RelativePathBuf) -> Box<RelativePath> {
+        path.into_boxed_relative_path()
+    }
+}
+
+impl From<Box<RelativePath>> for RelativePathBuf {
+    fn from(path: Box<RelativePath>) -> RelativePathBuf {
+        path.into_relative_path_buf()
+    }
+}
+
+impl From<&RelativePath> for Box<RelativePath> {
+    fn from(path: &RelativePath) -> Box<RelativePath> {
+        path.to_relative_path_buf().into_boxed_relative_path()
+    }
+}
+
+impl Clone for Box<RelativePath> {
+    fn clone(&self) -> Self {
+        self.to_relative_path_buf().into_boxed_relative_path()
+    }
+}
+
+impl From<&RelativePath> for Rc<RelativePath> {
+    fn from(path: &RelativePath) -> Rc<RelativePath> {
+        let rc: Rc<str> = Rc::from(&path.inner);
+        unsafe { Rc::from_raw(Rc::into_raw(rc) as *const RelativePath) }
+    }
+}
+
+impl From<RelativePathBuf> for Rc<RelativePath> {
+    fn from(path: RelativePathBuf) -> Rc<RelativePath> {
+        let rc: Rc<str> = Rc::from(path.inner);
+        unsafe { Rc::from_raw(Rc::into_raw(rc) as *const RelativePath) }
+    }
+}
+
+impl From<&RelativePath> for Arc<RelativePath> {
+    fn from(path: &RelativePath) -> Arc<RelativePath> {
+        let arc: Arc<str> = Arc::from(&path.inner);
+        unsafe { Arc::from_raw(Arc::into_raw(arc) as *const RelativePath) }
+    }
+}
+
+impl From<RelativePathBuf> for Arc<RelativePath> {
+    fn from(path: RelativePathBuf) -> Arc<RelativePath> {
+        let arc: Arc<str> = Arc::from(path.inner);
+        unsafe { Arc::from_raw(Arc::into_raw(arc) as *const RelativePath) }
+    }
+}
+
 /// A borrowed, immutable relative path.
 #[repr(transparent)]
 pub struct RelativePath {
@@ -631,10 +1500,43 @@ pub struct RelativePath {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StripPrefixError(());
 
-impl RelativePath {
-    /// Directly wraps a string slice as a `RelativePath` slice.
-    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> &RelativePath {
-        unsafe { &*(s.as_ref() as *const str as *const RelativePath) }
+impl fmt::Display for StripPrefixError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        "prefix not found".fmt(fmt)
+    }
+}
+
+impl error::Error for StripPrefixError {}
+
+/// The parts of a file name, as returned by [`RelativePath::file_name_parts`].
+///
+/// [`RelativePath::file_name_parts`]: struct.RelativePath.html#method.file_name_parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileNameParts<'a> {
+    /// The file name up until the first `.`, not counting a leading `.`.
+    pub stem: &'a str,
+    /// Each `.`-separated extension following the stem, in order.
+    pub extensions: Vec<&'a str>,
+}
+
+impl RelativePath {
+    /// Directly wraps a string slice as a `RelativePath` slice.
+    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> &RelativePath {
+        unsafe { &*(s.as_ref() as *const str as *const RelativePath) }
+    }
+
+    /// Try to wrap a byte slice as a `RelativePath` slice, validating that it is UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(Ok(RelativePath::new("foo/bar")), RelativePath::from_utf8(b"foo/bar"));
+    /// assert!(RelativePath::from_utf8(&[0x80]).is_err());
+    /// ```
+    pub fn from_utf8(bytes: &[u8]) -> Result<&RelativePath, str::Utf8Error> {
+        str::from_utf8(bytes).map(RelativePath::new)
     }
 
     /// Try to convert a [`Path`] to a `RelativePath` without allocating a buffer.
@@ -683,11 +1585,6 @@ impl RelativePath {
         Ok(rel)
     }
 
-    // The following (private!) function reveals the byte encoding used for str.
-    fn as_u8_slice(&self) -> &[u8] {
-        self.inner.as_bytes()
-    }
-
     /// Yields the underlying `str` slice.
     ///
     /// # Examples
@@ -701,6 +1598,61 @@ impl RelativePath {
         &self.inner
     }
 
+    /// Test if the underlying string is empty.
+    ///
+    /// Note that a path which only consists of separators, like `/`, is not empty even though it
+    /// has no components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("").is_empty());
+    /// assert!(!RelativePath::new("/").is_empty());
+    /// assert!(!RelativePath::new("foo").is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Yields the underlying `str` slice as a borrowed [`Cow`].
+    ///
+    /// Through [`Deref`], this is also available on [`RelativePathBuf`].
+    ///
+    /// [`Cow`]: std::borrow::Cow
+    /// [`Deref`]: std::ops::Deref
+    /// [`RelativePathBuf`]: RelativePathBuf
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    /// use std::borrow::Cow;
+    ///
+    /// assert_eq!(Cow::Borrowed("foo.txt"), RelativePath::new("foo.txt").as_cow());
+    ///
+    /// let buf = RelativePathBuf::from("foo.txt");
+    /// assert!(matches!(buf.as_cow(), Cow::Borrowed("foo.txt")));
+    /// ```
+    pub fn as_cow(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.inner)
+    }
+
+    /// Yields the underlying UTF-8 bytes of the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(RelativePath::new("foo.txt").as_bytes(), b"foo.txt");
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        self.inner.as_bytes()
+    }
+
     /// Returns an object that implements [`Display`].
     ///
     /// # Examples
@@ -713,7 +1665,7 @@ impl RelativePath {
     /// println!("{}", path.display());
     /// ```
     #[deprecated(note = "RelativePath implements std::fmt::Display directly")]
-    pub fn display(&self) -> Display {
+    pub fn display(&self) -> Display<'_> {
         Display { path: self }
     }
 
@@ -728,13 +1680,119 @@ impl RelativePath {
     ///
     /// let path = RelativePath::new("foo/bar");
     /// assert_eq!("foo/bar/baz", path.join("baz"));
+    /// assert_eq!("foo/bar/", path.join("").as_str());
     /// ```
     pub fn join<P: AsRef<RelativePath>>(&self, path: P) -> RelativePathBuf {
-        let mut out = self.to_relative_path_buf();
+        if self.inner.is_empty() {
+            return RelativePathBuf::from(path.as_ref().trim_sep_once());
+        }
+
+        let path = path.as_ref();
+        let mut inner = String::with_capacity(self.inner.len() + 1 + path.inner.len());
+        inner.push_str(&self.inner);
+
+        let mut out = RelativePathBuf { inner };
         out.push(path);
         out
     }
 
+    /// Join `path` onto `self`, validating it first.
+    ///
+    /// Unlike [`join`], which silently ignores a leading separator and allows
+    /// `path` to navigate anywhere, this is intended for paths coming from
+    /// untrusted sources.
+    ///
+    /// [`join`]: #method.join
+    ///
+    /// # Errors
+    ///
+    /// * [`JoinErrorKind::Absolute`] if `path` starts with a separator.
+    /// * [`JoinErrorKind::Escapes`] if a `..` component in `path` escapes
+    ///   `self`, as checked by [`is_lexically_safe`].
+    /// * [`JoinErrorKind::TooLong`] if the joined path would be longer than
+    ///   4096 bytes.
+    ///
+    /// [`JoinErrorKind::Absolute`]: enum.JoinErrorKind.html#variant.Absolute
+    /// [`JoinErrorKind::Escapes`]: enum.JoinErrorKind.html#variant.Escapes
+    /// [`JoinErrorKind::TooLong`]: enum.JoinErrorKind.html#variant.TooLong
+    /// [`is_lexically_safe`]: #method.is_lexically_safe
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{JoinErrorKind, RelativePath};
+    ///
+    /// let base = RelativePath::new("uploads");
+    ///
+    /// assert_eq!(Ok("uploads/a/b".into()), base.join_checked("a/b"));
+    /// assert_eq!(Ok("uploads/a/../b".into()), base.join_checked("a/../b"));
+    /// assert_eq!(Err(JoinErrorKind::Absolute.into()), base.join_checked("/etc"));
+    /// assert_eq!(Err(JoinErrorKind::Escapes.into()), base.join_checked("a/../../etc"));
+    /// assert_eq!(Err(JoinErrorKind::TooLong.into()), base.join_checked("a".repeat(5000)));
+    /// ```
+    pub fn join_checked<P: AsRef<RelativePath>>(
+        &self,
+        path: P,
+    ) -> Result<RelativePathBuf, JoinError> {
+        let path = path.as_ref();
+
+        if path.starts_with_sep() {
+            return Err(JoinErrorKind::Absolute.into());
+        }
+
+        if !path.is_lexically_safe() {
+            return Err(JoinErrorKind::Escapes.into());
+        }
+
+        let joined = self.join(path);
+
+        if joined.inner.len() > MAX_JOIN_LEN {
+            return Err(JoinErrorKind::TooLong.into());
+        }
+
+        Ok(joined)
+    }
+
+    /// Creates an owned [`RelativePathBuf`] with path adjoined to self if it is [`Some`], or a
+    /// copy of self otherwise.
+    ///
+    /// [`RelativePathBuf`]: RelativePathBuf
+    /// [`Some`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("foo/bar");
+    /// assert_eq!("foo/bar/baz", path.join_opt(Some("baz")));
+    /// assert_eq!("foo/bar", path.join_opt(None::<&str>));
+    /// ```
+    pub fn join_opt<P: AsRef<RelativePath>>(&self, path: Option<P>) -> RelativePathBuf {
+        match path {
+            Some(path) => self.join(path),
+            None => self.to_relative_path_buf(),
+        }
+    }
+
+    /// Creates an owned [`RelativePathBuf`] with self adjoined to `root`.
+    ///
+    /// This is the same as `root.join(self)`.
+    ///
+    /// [`RelativePathBuf`]: RelativePathBuf
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("bar/baz");
+    /// assert_eq!("foo/bar/baz", path.with_root("foo"));
+    /// ```
+    pub fn with_root<P: AsRef<RelativePath>>(&self, root: P) -> RelativePathBuf {
+        root.as_ref().join(self)
+    }
+
     /// Iterate over all components in this relative path.
     ///
     /// # Examples
@@ -750,7 +1808,7 @@ impl RelativePath {
     /// assert_eq!(Some(Component::Normal("baz")), it.next());
     /// assert_eq!(None, it.next());
     /// ```
-    pub fn components(&self) -> Components {
+    pub fn components(&self) -> Components<'_> {
         Components::new(&self.inner)
     }
 
@@ -773,12 +1831,59 @@ impl RelativePath {
     /// assert_eq!(it.next(), Some("foo.txt"));
     /// assert_eq!(it.next(), None)
     /// ```
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
             inner: self.components(),
         }
     }
 
+    /// Produces an iterator over the path's components as owned [`SmolStr`] values.
+    ///
+    /// Most path components are short enough to be stored inline, so collecting them does not
+    /// need a heap allocation per component.
+    ///
+    /// This requires the `smol_str` feature.
+    ///
+    /// [`SmolStr`]: https://docs.rs/smol_str
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    /// use smol_str::SmolStr;
+    ///
+    /// let path = RelativePath::new("foo//bar/baz");
+    /// let components: Vec<SmolStr> = path.smol_components().collect();
+    /// assert_eq!(components, ["foo", "bar", "baz"]);
+    /// ```
+    #[cfg(feature = "smol_str")]
+    pub fn smol_components(&self) -> impl Iterator<Item = smol_str::SmolStr> + '_ {
+        self.iter().map(smol_str::SmolStr::new)
+    }
+
+    /// Collects the path's components viewed as [`str`] slices into a [`SmallVec`].
+    ///
+    /// Paths with up to 8 components are stored inline, without allocating.
+    ///
+    /// This requires the `smallvec` feature.
+    ///
+    /// [`str`]: primitive@str
+    /// [`SmallVec`]: https://docs.rs/smallvec
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// let components = RelativePath::new("foo//bar/baz").components_smallvec();
+    /// assert!(!components.spilled());
+    /// assert_eq!(&components[..], &["foo", "bar", "baz"]);
+    /// ```
+    #[cfg(feature = "smallvec")]
+    pub fn components_smallvec(&self) -> smallvec::SmallVec<[&str; 8]> {
+        self.iter().collect()
+    }
+
     /// Convert to an owned [`RelativePathBuf`].
     ///
     /// [`RelativePathBuf`]: RelativePathBuf
@@ -786,6 +1891,50 @@ impl RelativePath {
         RelativePathBuf::from(self.inner.to_string())
     }
 
+    /// Converts a [`Box<RelativePath>`][`Box`] into a [`RelativePathBuf`] without copying or
+    /// allocating.
+    ///
+    /// [`Box`]: std::boxed::Box
+    /// [`RelativePathBuf`]: RelativePathBuf
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let boxed: Box<RelativePath> = RelativePathBuf::from("foo/bar").into_boxed_relative_path();
+    /// let mut path = boxed.into_relative_path_buf();
+    /// path.push("baz");
+    /// assert_eq!("foo/bar/baz", path);
+    /// ```
+    pub fn into_relative_path_buf(self: Box<RelativePath>) -> RelativePathBuf {
+        let raw = Box::into_raw(self) as *mut str;
+        let inner = unsafe { Box::from_raw(raw) };
+
+        RelativePathBuf {
+            inner: inner.into_string(),
+        }
+    }
+
+    /// Convert to an owned, normalized [`RelativePathBuf`].
+    ///
+    /// This is the same as [`normalize`]: redundant separators are collapsed, `.` and `..` are
+    /// resolved, and leading and trailing separators are removed.
+    ///
+    /// [`RelativePathBuf`]: RelativePathBuf
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!("foo/baz", RelativePath::new("/foo//bar/../baz/").to_normalized_buf().as_str());
+    /// ```
+    pub fn to_normalized_buf(&self) -> RelativePathBuf {
+        self.normalize()
+    }
+
     /// Build an owned `PathBuf` relative to `path` for the current relative path.
     ///
     /// # Examples
@@ -803,6 +1952,103 @@ impl RelativePath {
         p
     }
 
+    /// Build an owned [`PathBuf`] relative to `relative_to`, handling `..` components according
+    /// to `policy`.
+    ///
+    /// With [`ParentDirPolicy::Literal`] this is the same as [`to_path`].
+    ///
+    /// [`PathBuf`]: std::path::PathBuf
+    /// [`ParentDirPolicy::Literal`]: enum.ParentDirPolicy.html#variant.Literal
+    /// [`to_path`]: #method.to_path
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ParentDirError`] if `policy` is [`ParentDirPolicy::Reject`] and the path
+    /// contains a `..` component.
+    ///
+    /// [`ParentDirError`]: struct.ParentDirError.html
+    /// [`ParentDirPolicy::Reject`]: enum.ParentDirPolicy.html#variant.Reject
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{ParentDirPolicy, RelativePath};
+    /// use std::path::Path;
+    ///
+    /// let path = RelativePath::new("../x");
+    /// let base = Path::new("root").join("dir");
+    ///
+    /// assert_eq!(
+    ///     Ok(Path::new("root").join("dir").join("..").join("x")),
+    ///     path.to_path_with(&base, ParentDirPolicy::Literal)
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Ok(Path::new("root").join("x")),
+    ///     path.to_path_with(&base, ParentDirPolicy::Resolve)
+    /// );
+    ///
+    /// assert!(path.to_path_with(&base, ParentDirPolicy::Reject).is_err());
+    /// ```
+    pub fn to_path_with<P: AsRef<path::Path>>(
+        &self,
+        relative_to: P,
+        policy: ParentDirPolicy,
+    ) -> Result<path::PathBuf, ParentDirError> {
+        let mut p = relative_to.as_ref().to_path_buf();
+
+        match policy {
+            ParentDirPolicy::Literal => p.extend(self.components().map(|c| c.as_str())),
+            ParentDirPolicy::Resolve => logical_extend(&mut p, self.components()),
+            ParentDirPolicy::Reject => {
+                for c in self.components() {
+                    if c == Component::ParentDir {
+                        return Err(ParentDirError(()));
+                    }
+
+                    p.push(c.as_str());
+                }
+            }
+        }
+
+        Ok(p)
+    }
+
+    /// Build an owned `PathBuf` relative to `base`, lexically resolving `.` and `..` components
+    /// as it goes.
+    ///
+    /// Unlike [`to_path`], which extends `base` verbatim, a `..` component removes the last
+    /// normal component built so far, including those of `base`. A `..` which can't be resolved
+    /// is kept as-is. The filesystem is never consulted, so symbolic links are not taken into
+    /// account.
+    ///
+    /// This is the same as [`to_path_with`] using [`ParentDirPolicy::Resolve`].
+    ///
+    /// [`to_path`]: #method.to_path
+    /// [`to_path_with`]: #method.to_path_with
+    /// [`ParentDirPolicy::Resolve`]: enum.ParentDirPolicy.html#variant.Resolve
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::path::Path;
+    ///
+    /// let path = RelativePath::new("a/../b").to_logical_path(".");
+    /// assert_eq!(Path::new(".").join("b"), path);
+    ///
+    /// let path = RelativePath::new("../../c/./d").to_logical_path(Path::new("root").join("dir"));
+    /// assert_eq!(Path::new("c").join("d"), path);
+    ///
+    /// let path = RelativePath::new("../x").to_logical_path("");
+    /// assert_eq!(Path::new("..").join("x"), path);
+    /// ```
+    pub fn to_logical_path<P: AsRef<path::Path>>(&self, base: P) -> path::PathBuf {
+        let mut p = base.as_ref().to_path_buf();
+        logical_extend(&mut p, self.components());
+        p
+    }
+
     /// Returns a relative path, without its final component if there is one.
     ///
     /// # Examples
@@ -813,6 +2059,7 @@ impl RelativePath {
     /// assert_eq!(Some(RelativePath::new("foo")), RelativePath::new("foo/bar").parent());
     /// assert_eq!(Some(RelativePath::new("")), RelativePath::new("foo").parent());
     /// assert_eq!(None, RelativePath::new("").parent());
+    /// assert_eq!(Some(RelativePath::new("foo")), RelativePath::new("foo///bar").parent());
     /// ```
     pub fn parent(&self) -> Option<&RelativePath> {
         use self::Component::*;
@@ -826,12 +2073,62 @@ impl RelativePath {
         Some(it.as_relative_path())
     }
 
+    /// Produces an iterator over `self` and its ancestors, as given by repeatedly calling
+    /// [`parent`], which stops before any ancestor with fewer than `min_depth` components.
+    ///
+    /// [`parent`]: #method.parent
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/b/c/d");
+    ///
+    /// let ancestors = path.ancestors_to_depth(1).collect::<Vec<_>>();
+    /// assert_eq!(ancestors, ["a/b/c/d", "a/b/c", "a/b", "a"]);
+    ///
+    /// let ancestors = path.ancestors_to_depth(0).collect::<Vec<_>>();
+    /// assert_eq!(ancestors, ["a/b/c/d", "a/b/c", "a/b", "a", ""]);
+    ///
+    /// assert_eq!(0, path.ancestors_to_depth(5).count());
+    /// ```
+    pub fn ancestors_to_depth(&self, min_depth: usize) -> impl Iterator<Item = &RelativePath> {
+        iter::successors(Some(self), |p| p.parent())
+            .take_while(move |p| p.components().count() >= min_depth)
+    }
+
+    /// Returns how many levels up the first ancestor matching `f` is, where `0` is `self`.
+    ///
+    /// Ancestors are visited like in [`ancestors_to_depth`], ending with the empty path.
+    ///
+    /// [`ancestors_to_depth`]: #method.ancestors_to_depth
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("crates/foo/src/bin");
+    ///
+    /// assert_eq!(Some(2), path.ancestor_distance_to(|p| p.ends_with("foo")));
+    /// assert_eq!(Some(0), path.ancestor_distance_to(|p| p.ends_with("bin")));
+    /// assert_eq!(Some(4), path.ancestor_distance_to(|p| p.as_str().is_empty()));
+    /// assert_eq!(None, path.ancestor_distance_to(|p| p.ends_with("bar")));
+    /// ```
+    pub fn ancestor_distance_to<F>(&self, f: F) -> Option<usize>
+    where
+        F: Fn(&RelativePath) -> bool,
+    {
+        iter::successors(Some(self), |p| p.parent()).position(f)
+    }
+
     /// Returns the final component of the `RelativePath`, if there is one.
     ///
     /// If the path is a normal file, this is the file name. If it's the path of a directory, this
-    /// is the directory name.
+    /// is the directory name. Trailing separators are ignored, so `a/b/` names `b`.
     ///
-    /// Returns [`None`] If the path terminates in `..`.
+    /// Returns [`None`] if the path is empty or terminates in `..`.
     ///
     /// [`None`]: std::option::Option
     ///
@@ -847,6 +2144,8 @@ impl RelativePath {
     /// assert_eq!(Some("foo.txt"), RelativePath::new("foo.txt/.//").file_name());
     /// assert_eq!(None, RelativePath::new("foo.txt/..").file_name());
     /// assert_eq!(None, RelativePath::new("/").file_name());
+    /// assert_eq!(None, RelativePath::new("").file_name());
+    /// assert_eq!(Some("foo"), RelativePath::new("foo/").file_name());
     /// ```
     pub fn file_name(&self) -> Option<&str> {
         use self::Component::*;
@@ -884,19 +2183,44 @@ impl RelativePath {
     /// assert_eq!(path.strip_prefix("test"), Ok(RelativePath::new("haha/foo.txt")));
     /// assert_eq!(path.strip_prefix("test").is_ok(), true);
     /// assert_eq!(path.strip_prefix("haha").is_ok(), false);
+    ///
+    /// let path = RelativePath::new("test///haha//foo.txt");
+    /// assert_eq!("haha//foo.txt", path.strip_prefix("test").unwrap().as_str());
+    /// assert_eq!("prefix not found", path.strip_prefix("foo").unwrap_err().to_string());
     /// ```
-    pub fn strip_prefix<'a, P: ?Sized>(
-        &'a self,
-        base: &'a P,
-    ) -> Result<&'a RelativePath, StripPrefixError>
+    pub fn strip_prefix<'a, P>(&'a self, base: &'a P) -> Result<&'a RelativePath, StripPrefixError>
     where
-        P: AsRef<RelativePath>,
+        P: ?Sized + AsRef<RelativePath>,
     {
         iter_after(self.components(), base.as_ref().components())
             .map(|c| c.as_relative_path())
             .ok_or(StripPrefixError(()))
     }
 
+    /// Like [`strip_prefix`], but matches components using ASCII case-insensitive comparison.
+    ///
+    /// The returned remainder borrows from `self`, preserving its original casing.
+    ///
+    /// [`strip_prefix`]: #method.strip_prefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("Test/Haha/Foo.txt");
+    ///
+    /// assert_eq!(Some(RelativePath::new("Haha/Foo.txt")), path.strip_prefix_ci("test"));
+    /// assert_eq!(Some(RelativePath::new("Foo.txt")), path.strip_prefix_ci("TEST/haha"));
+    /// assert_eq!(None, path.strip_prefix_ci("haha"));
+    /// ```
+    pub fn strip_prefix_ci<P: AsRef<RelativePath>>(&self, base: P) -> Option<&RelativePath> {
+        iter_after_by(self.components(), base.as_ref().components(), |a, b| {
+            a.as_str().eq_ignore_ascii_case(b.as_str())
+        })
+        .map(|c| c.as_relative_path())
+    }
+
     /// Determines whether `base` is a prefix of `self`.
     ///
     /// Only considers whole path components to match.
@@ -933,20 +2257,138 @@ impl RelativePath {
         iter_after(self.components().rev(), child.as_ref().components().rev()).is_some()
     }
 
-    /// Creates an owned [`RelativePathBuf`] like `self` but with the given file name.
+    /// Find the longest of `roots` which is a prefix of `self`.
     ///
-    /// See [`RelativePathBuf::set_file_name`] for more details.
-    ///
-    /// [`RelativePathBuf`]: RelativePathBuf
-    /// [`RelativePathBuf::set_file_name`]: struct.RelativePathBuf.html#method.set_file_name
+    /// Roots are compared by their number of components. If several roots of the same length
+    /// match, the first one is returned.
     ///
     /// # Examples
     ///
     /// ```
-    /// use relative_path::{RelativePath, RelativePathBuf};
+    /// use relative_path::RelativePath;
     ///
-    /// let path = RelativePath::new("tmp/foo.txt");
-    /// assert_eq!(path.with_file_name("bar.txt"), RelativePathBuf::from("tmp/bar.txt"));
+    /// let roots = [RelativePath::new("a"), RelativePath::new("a/b")];
+    ///
+    /// let path = RelativePath::new("a/b/c");
+    /// assert_eq!(Some(RelativePath::new("a/b")), path.longest_prefix_in(roots.iter().copied()));
+    ///
+    /// let path = RelativePath::new("a/c");
+    /// assert_eq!(Some(RelativePath::new("a")), path.longest_prefix_in(roots.iter().copied()));
+    ///
+    /// let path = RelativePath::new("b/c");
+    /// assert_eq!(None, path.longest_prefix_in(roots.iter().copied()));
+    /// ```
+    pub fn longest_prefix_in<'a, I>(&self, roots: I) -> Option<&'a RelativePath>
+    where
+        I: IntoIterator<Item = &'a RelativePath>,
+    {
+        let mut longest: Option<(usize, &'a RelativePath)> = None;
+
+        for root in roots {
+            if !self.starts_with(root) {
+                continue;
+            }
+
+            let depth = root.components().count();
+
+            match longest {
+                Some((longest_depth, _)) if longest_depth >= depth => (),
+                _ => longest = Some((depth, root)),
+            }
+        }
+
+        longest.map(|(_, root)| root)
+    }
+
+    /// Find the longest of `suffixes` which is a suffix of `self`.
+    ///
+    /// This is the suffix analog of [`longest_prefix_in`]. Suffixes are compared by their number
+    /// of components. If several suffixes of the same length match, the first one is returned.
+    ///
+    /// [`longest_prefix_in`]: #method.longest_prefix_in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// let suffixes = [RelativePath::new("c"), RelativePath::new("b/c")];
+    ///
+    /// let path = RelativePath::new("a/b/c");
+    /// assert_eq!(Some(RelativePath::new("b/c")), path.longest_suffix_in(suffixes));
+    ///
+    /// let path = RelativePath::new("a/c");
+    /// assert_eq!(Some(RelativePath::new("c")), path.longest_suffix_in(suffixes));
+    ///
+    /// let path = RelativePath::new("b/a");
+    /// assert_eq!(None, path.longest_suffix_in(suffixes));
+    /// ```
+    pub fn longest_suffix_in<'a, I>(&self, suffixes: I) -> Option<&'a RelativePath>
+    where
+        I: IntoIterator<Item = &'a RelativePath>,
+    {
+        let mut longest: Option<(usize, &'a RelativePath)> = None;
+
+        for suffix in suffixes {
+            if !self.ends_with(suffix) {
+                continue;
+            }
+
+            let depth = suffix.components().count();
+
+            match longest {
+                Some((longest_depth, _)) if longest_depth >= depth => (),
+                _ => longest = Some((depth, suffix)),
+            }
+        }
+
+        longest.map(|(_, suffix)| suffix)
+    }
+
+    /// Creates an owned [`RelativePathBuf`] like `self` but with its first component replaced by
+    /// `name`.
+    ///
+    /// If the path is empty, the result consists of just `name`.
+    ///
+    /// [`RelativePathBuf`]: RelativePathBuf
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!("lib/a/b", RelativePath::new("src/a/b").replace_first_component("lib"));
+    /// assert_eq!("lib", RelativePath::new("src").replace_first_component("lib"));
+    /// assert_eq!("lib", RelativePath::new("").replace_first_component("lib"));
+    /// ```
+    pub fn replace_first_component(&self, name: &str) -> RelativePathBuf {
+        let mut it = self.components();
+        it.next();
+
+        let mut buf = RelativePathBuf::from(name);
+        let rest = it.as_relative_path();
+
+        if !rest.inner.is_empty() {
+            buf.push(rest);
+        }
+
+        buf
+    }
+
+    /// Creates an owned [`RelativePathBuf`] like `self` but with the given file name.
+    ///
+    /// See [`RelativePathBuf::set_file_name`] for more details.
+    ///
+    /// [`RelativePathBuf`]: RelativePathBuf
+    /// [`RelativePathBuf::set_file_name`]: struct.RelativePathBuf.html#method.set_file_name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::{RelativePath, RelativePathBuf};
+    ///
+    /// let path = RelativePath::new("tmp/foo.txt");
+    /// assert_eq!(path.with_file_name("bar.txt"), RelativePathBuf::from("tmp/bar.txt"));
     ///
     /// let path = RelativePath::new("tmp");
     /// assert_eq!(path.with_file_name("var"), RelativePathBuf::from("var"));
@@ -978,6 +2420,7 @@ impl RelativePath {
     /// let path = RelativePath::new("foo.rs");
     ///
     /// assert_eq!("foo", path.file_stem().unwrap());
+    /// assert_eq!(Some(".gitignore"), RelativePath::new("a/.gitignore").file_stem());
     /// ```
     pub fn file_stem(&self) -> Option<&str> {
         self.file_name()
@@ -1005,6 +2448,7 @@ impl RelativePath {
     /// assert_eq!(Some("rs"), RelativePath::new("foo.rs").extension());
     /// assert_eq!(None, RelativePath::new(".rs").extension());
     /// assert_eq!(Some("rs"), RelativePath::new("foo.rs/.").extension());
+    /// assert_eq!(None, RelativePath::new("a/.gitignore").extension());
     /// ```
     pub fn extension(&self) -> Option<&str> {
         self.file_name()
@@ -1012,6 +2456,28 @@ impl RelativePath {
             .and_then(|(before, after)| before.and(after))
     }
 
+    /// Returns the byte range of [`self.extension`] within the path, if there is an extension.
+    ///
+    /// [`self.extension`]: struct.RelativePath.html#method.extension
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/b.txt");
+    /// assert_eq!(Some(4..7), path.extension_span());
+    /// assert_eq!("txt", &path.as_str()[4..7]);
+    ///
+    /// assert_eq!(Some(8..10), RelativePath::new("a/b.tar.gz/").extension_span());
+    /// assert_eq!(None, RelativePath::new("a/.txt").extension_span());
+    /// ```
+    pub fn extension_span(&self) -> Option<ops::Range<usize>> {
+        let extension = self.extension()?;
+        let start = self.offset_of(extension);
+        Some(start..start + extension.len())
+    }
+
     /// Creates an owned [`RelativePathBuf`] like `self` but with the given extension.
     ///
     /// See [`RelativePathBuf::set_extension`] for more details.
@@ -1040,60 +2506,1479 @@ impl RelativePath {
     /// ```rust
     /// use relative_path::RelativePath;
     ///
-    /// assert_eq!(
-    ///     RelativePath::new("foo/baz.txt"),
-    ///     RelativePath::new("foo/bar").join_normalized("../baz.txt").as_relative_path()
-    /// );
-    ///
-    /// assert_eq!(
-    ///     RelativePath::new("../foo/baz.txt"),
-    ///     RelativePath::new("../foo/bar").join_normalized("../baz.txt").as_relative_path()
-    /// );
+    /// assert_eq!(
+    ///     RelativePath::new("foo/baz.txt"),
+    ///     RelativePath::new("foo/bar").join_normalized("../baz.txt").as_relative_path()
+    /// );
+    ///
+    /// assert_eq!(
+    ///     RelativePath::new("../foo/baz.txt"),
+    ///     RelativePath::new("../foo/bar").join_normalized("../baz.txt").as_relative_path()
+    /// );
+    /// ```
+    pub fn join_normalized<P: AsRef<RelativePath>>(&self, path: P) -> RelativePathBuf {
+        let mut stack = Vec::new();
+        relative_traversal(&mut stack, self.components());
+        relative_traversal(&mut stack, path.as_ref().components());
+        let string = stack
+            .into_iter()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        RelativePathBuf::from(string)
+    }
+
+    /// Join `self` with the given path and return the normalized result as a `String`.
+    ///
+    /// This is the same as [`join_normalized`], returning the string directly.
+    ///
+    /// [`join_normalized`]: #method.join_normalized
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("foo/bar");
+    /// assert_eq!("foo/baz.txt", path.join_to_string("../baz.txt"));
+    /// assert_eq!("foo/bar/baz", path.join_to_string("./baz/"));
+    /// ```
+    pub fn join_to_string<P: AsRef<RelativePath>>(&self, path: P) -> String {
+        self.join_normalized(path).inner
+    }
+
+    /// Return an owned `RelativePathBuf`, with all non-normal components moved to the beginning of
+    /// the path.
+    ///
+    /// This permits for a normalized representation of different relative components.
+    ///
+    /// Normalization is a _destructive_ operation if the path references an actual filesystem
+    /// path.
+    /// An example of this is symlinks under unix, a path like `foo/../bar` might reference a
+    /// different location other than `./bar`.
+    ///
+    /// Normalization is a logical operation that is only valid if the relative path is part of
+    /// some context which doesn't have semantics that causes it to break, like symbolic links.
+    /// It is purely lexical, and unlike [`std::fs::canonicalize`] never touches the filesystem.
+    /// See [`is_lexically_safe`] to check that a path doesn't escape the directory it's relative
+    /// to.
+    ///
+    /// [`std::fs::canonicalize`]: std::fs::canonicalize
+    /// [`is_lexically_safe`]: #method.is_lexically_safe
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(
+    ///     RelativePath::new("../foo/baz.txt"),
+    ///     RelativePath::new("../foo/./bar/../baz.txt").normalize().as_relative_path()
+    /// );
+    /// ```
+    pub fn normalize(&self) -> RelativePathBuf {
+        let mut stack = Vec::new();
+        relative_traversal(&mut stack, self.components());
+        let string = stack
+            .into_iter()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        let buf = RelativePathBuf::from(string);
+        debug_assert!(buf.is_normalized(), "normalized {:?} to {:?}", self, buf);
+        buf
+    }
+
+    /// Like [`normalize`], but fails if the result would start with more than `max_up` `..`
+    /// components.
+    ///
+    /// This allows limited navigation above the directory the path is relative to.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`TooManyUpError`] if the normalized path has more than `max_up` leading `..`
+    /// components.
+    ///
+    /// [`TooManyUpError`]: struct.TooManyUpError.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(Ok("../shared".into()), RelativePath::new("../shared").normalize_max_up(1));
+    /// assert_eq!(Ok("x".into()), RelativePath::new("a/b/../../x").normalize_max_up(0));
+    /// assert!(RelativePath::new("../../../etc").normalize_max_up(1).is_err());
+    /// ```
+    pub fn normalize_max_up(&self, max_up: usize) -> Result<RelativePathBuf, TooManyUpError> {
+        let buf = self.normalize();
+
+        let up = buf
+            .components()
+            .take_while(|c| *c == Component::ParentDir)
+            .count();
+
+        if up > max_up {
+            return Err(TooManyUpError(()));
+        }
+
+        Ok(buf)
+    }
+
+    /// Check if the path is already in the form produced by [`normalize`].
+    ///
+    /// That is, it has no leading, trailing or redundant separators, no `.` components, and any
+    /// `..` components come before all normal components.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("../foo/bar").is_normalized());
+    /// assert!(RelativePath::new("").is_normalized());
+    /// assert!(!RelativePath::new("foo/../bar").is_normalized());
+    /// assert!(!RelativePath::new("./foo").is_normalized());
+    /// assert!(!RelativePath::new("foo//bar").is_normalized());
+    /// assert!(!RelativePath::new("foo/").is_normalized());
+    /// ```
+    pub fn is_normalized(&self) -> bool {
+        use self::Component::*;
+
+        if self.starts_with_sep() || self.has_trailing_separator() || self.inner.contains("//") {
+            return false;
+        }
+
+        let mut seen_normal = false;
+
+        for c in self.components() {
+            match c {
+                CurDir => return false,
+                ParentDir if seen_normal => return false,
+                ParentDir => (),
+                Normal(_) => seen_normal = true,
+            }
+        }
+
+        true
+    }
+
+    /// Return an owned `RelativePathBuf` with `.` and `..` components resolved, but the layout
+    /// of separators otherwise left untouched.
+    ///
+    /// Unlike [`normalize`], this keeps redundant, leading and trailing separators. Each removed
+    /// component takes the separators following it along with it.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!("a//b/", RelativePath::new("a//./b/").resolve_dots().as_str());
+    /// assert_eq!("/b//c", RelativePath::new("/./a/../b//c").resolve_dots().as_str());
+    /// assert_eq!("../b", RelativePath::new("../a/../b").resolve_dots().as_str());
+    /// assert_eq!("a", RelativePath::new("a/b/..").resolve_dots().as_str());
+    /// assert_eq!("a", RelativePath::new("a/.").resolve_dots().as_str());
+    /// ```
+    pub fn resolve_dots(&self) -> RelativePathBuf {
+        use self::Component::*;
+
+        let mut rest = self.inner.trim_start_matches(SEP);
+        let leading = &self.inner[..self.inner.len() - rest.len()];
+
+        // Each entry is a component, together with the separators following it.
+        let mut stack = Vec::new();
+
+        while !rest.is_empty() {
+            let end = rest.find(SEP).unwrap_or(rest.len());
+            let after = rest[end..].trim_start_matches(SEP);
+            let name = &rest[..end];
+            let piece = &rest[..rest.len() - after.len()];
+            rest = after;
+
+            match name {
+                CURRENT_STR => (),
+                PARENT_STR => match stack.last() {
+                    Some(&(Normal(_), _)) => {
+                        stack.pop();
+                    }
+                    _ => stack.push((ParentDir, piece)),
+                },
+                name => stack.push((Normal(name), piece)),
+            }
+        }
+
+        let mut out = String::from(leading);
+
+        if let Some((&(_, last), init)) = stack.split_last() {
+            for &(_, piece) in init {
+                out.push_str(piece);
+            }
+
+            // The last remaining piece may have been followed by a removed component, in which
+            // case its separators must not end up trailing the output.
+            if self.inner.ends_with(SEP) {
+                out.push_str(last);
+            } else {
+                out.push_str(last.trim_end_matches(SEP));
+            }
+        }
+
+        RelativePathBuf::from(out)
+    }
+
+    /// Check that no `..` component in the path, resolved lexically, escapes the directory the
+    /// path is relative to.
+    ///
+    /// Like [`normalize`], this doesn't account for symbolic links.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("foo/../bar").is_lexically_safe());
+    /// assert!(RelativePath::new("foo/./bar/..").is_lexically_safe());
+    /// assert!(!RelativePath::new("../bar").is_lexically_safe());
+    /// assert!(!RelativePath::new("foo/../../bar").is_lexically_safe());
+    /// ```
+    pub fn is_lexically_safe(&self) -> bool {
+        use self::Component::*;
+
+        let mut depth = 0usize;
+
+        for c in self.components() {
+            match c {
+                CurDir => (),
+                ParentDir => match depth.checked_sub(1) {
+                    Some(d) => depth = d,
+                    None => return false,
+                },
+                Normal(_) => depth += 1,
+            }
+        }
+
+        true
+    }
+
+    /// Like [`normalize`], but preserves a single trailing separator if the path has one.
+    ///
+    /// This is useful where a trailing separator is significant, like a directory marker.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/./b//").normalize_preserving_trailing_slash();
+    /// assert_eq!("a/b/", path.as_str());
+    ///
+    /// let path = RelativePath::new("a//b").normalize_preserving_trailing_slash();
+    /// assert_eq!("a/b", path.as_str());
+    ///
+    /// let path = RelativePath::new("a/../").normalize_preserving_trailing_slash();
+    /// assert_eq!("", path.as_str());
+    /// ```
+    pub fn normalize_preserving_trailing_slash(&self) -> RelativePathBuf {
+        let mut buf = self.normalize();
+
+        if self.has_trailing_separator() && !buf.inner.is_empty() {
+            buf.inner.push(SEP);
+        }
+
+        buf
+    }
+
+    /// Join `frag` onto the current path, and build an owned `PathBuf` relative to `base` from
+    /// the result.
+    ///
+    /// Unlike `self.join(frag).to_path(base)`, the joined path is normalized and any leading `..`
+    /// components are resolved by popping components off `base`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::path::Path;
+    ///
+    /// let path = RelativePath::new("config").joined_relative_of("../data/./x.txt", "root");
+    /// assert_eq!(Path::new("root").join("data").join("x.txt"), path);
+    ///
+    /// let path = RelativePath::new("config").joined_relative_of("../../x.txt", "root/dir");
+    /// assert_eq!(Path::new("root").join("x.txt"), path);
+    /// ```
+    pub fn joined_relative_of<P, B>(&self, frag: P, base: B) -> path::PathBuf
+    where
+        P: AsRef<RelativePath>,
+        B: AsRef<path::Path>,
+    {
+        let mut stack = Vec::new();
+        relative_traversal(&mut stack, self.components());
+        relative_traversal(&mut stack, frag.as_ref().components());
+
+        let mut p = base.as_ref().to_path_buf();
+        logical_extend(&mut p, stack);
+        p
+    }
+
+    /// Check if any component of the path contains an ASCII control character (`0x00` to `0x1F`,
+    /// or `0x7F`).
+    ///
+    /// Such characters are not permitted in file names on common filesystems, so this can be used
+    /// to reject dangerous names before creating files. See [`has_forbidden_chars`] for checking
+    /// against a custom set of characters.
+    ///
+    /// [`has_forbidden_chars`]: #method.has_forbidden_chars
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(!RelativePath::new("foo/bar.txt").has_control_chars());
+    /// assert!(RelativePath::new("foo/bar\n.txt").has_control_chars());
+    /// assert!(RelativePath::new("foo\0/bar").has_control_chars());
+    /// assert!(RelativePath::new("foo/bar\u{7f}").has_control_chars());
+    /// ```
+    pub fn has_control_chars(&self) -> bool {
+        self.iter()
+            .any(|c| c.contains(|c: char| c.is_ascii_control()))
+    }
+
+    /// Check if any component of the path contains one of the `forbidden` characters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// const WINDOWS: &[char] = &['<', '>', ':', '"', '\\', '|', '?', '*'];
+    ///
+    /// assert!(!RelativePath::new("foo/bar.txt").has_forbidden_chars(WINDOWS));
+    /// assert!(RelativePath::new("foo/bar?.txt").has_forbidden_chars(WINDOWS));
+    /// assert!(RelativePath::new("c:/bar.txt").has_forbidden_chars(WINDOWS));
+    /// ```
+    pub fn has_forbidden_chars(&self, forbidden: &[char]) -> bool {
+        self.iter().any(|c| c.contains(forbidden))
+    }
+
+    /// Find the first position at which the components of `self` and `other` differ.
+    ///
+    /// Returns the index of the differing component, together with the component of each path at
+    /// that position. The side which has run out of components is [`None`]. If the paths have
+    /// equal components, returns [`None`].
+    ///
+    /// [`None`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let a = RelativePath::new("foo/bar/baz");
+    ///
+    /// assert_eq!(Some((1, Some("bar"), Some("biz"))), a.first_difference("foo/biz".as_ref()));
+    /// assert_eq!(Some((2, Some("baz"), None)), a.first_difference("foo/bar".as_ref()));
+    /// assert_eq!(None, a.first_difference("foo//bar/baz/".as_ref()));
+    /// ```
+    pub fn first_difference<'a>(
+        &'a self,
+        other: &'a RelativePath,
+    ) -> Option<(usize, Option<&'a str>, Option<&'a str>)> {
+        let mut a = self.components();
+        let mut b = other.components();
+        let mut index = 0;
+
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return None,
+                (Some(x), Some(y)) if x == y => (),
+                (x, y) => return Some((index, x.map(Component::as_str), y.map(Component::as_str))),
+            }
+
+            index += 1;
+        }
+    }
+
+    /// Compare the components of `self` and `other` using a custom equality function.
+    ///
+    /// Returns `true` if both paths have the same number of components, and `eq` returns `true`
+    /// for each pair of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let a = RelativePath::new("Foo/BAR");
+    /// let b = RelativePath::new("foo//bar/");
+    ///
+    /// assert!(!a.components_eq_by(b, |a, b| a == b));
+    /// assert!(a.components_eq_by(b, |a, b| a.eq_ignore_ascii_case(b)));
+    /// assert!(!a.components_eq_by("foo".as_ref(), |a, b| a.eq_ignore_ascii_case(b)));
+    /// ```
+    pub fn components_eq_by<F>(&self, other: &RelativePath, eq: F) -> bool
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        let mut a = self.iter();
+        let mut b = other.iter();
+
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return true,
+                (Some(x), Some(y)) if eq(x, y) => (),
+                _ => return false,
+            }
+        }
+    }
+
+    /// Compare the components of `self` to a sequence of segments.
+    ///
+    /// Each segment is compared exactly to one component, so a segment containing a separator
+    /// never matches. Comparison stops at the first mismatch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a//b/c/");
+    ///
+    /// assert!(path.components_eq(["a", "b", "c"]));
+    /// assert!(!path.components_eq(["a", "b"]));
+    /// assert!(!path.components_eq(["a/b", "c"]));
+    /// ```
+    pub fn components_eq<'a, I>(&self, segments: I) -> bool
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.iter().eq(segments)
+    }
+
+    /// Test if the leading components of `self` are equal to a sequence of segments.
+    ///
+    /// Like [`components_eq`], each segment is compared exactly to one component.
+    ///
+    /// [`components_eq`]: #method.components_eq
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("src/gen/foo.rs");
+    /// let prefix = vec![String::from("src"), String::from("gen")];
+    ///
+    /// assert!(path.starts_with_components(["src", "gen"]));
+    /// assert!(path.starts_with_components(prefix.iter().map(String::as_str)));
+    /// assert!(path.starts_with_components([]));
+    /// assert!(!path.starts_with_components(["src", "ge"]));
+    /// assert!(!path.starts_with_components(["src", "gen", "foo.rs", "bar"]));
+    /// ```
+    pub fn starts_with_components<'a, I>(&self, prefix: I) -> bool
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        iter_after_by(self.iter(), prefix.into_iter(), |a, b| a == b).is_some()
+    }
+
+    /// Test if the components of this path are matched by the given sequence of `matchers`.
+    ///
+    /// Each matcher is tested against the component at the corresponding position, and the path
+    /// must have exactly as many components as there are matchers. The exception is matchers for
+    /// which [`SegmentMatcher::matches_any_depth`] returns `true`, like [`AnyDepth`], which match
+    /// any number of components.
+    ///
+    /// [`SegmentMatcher::matches_any_depth`]: trait.SegmentMatcher.html#method.matches_any_depth
+    /// [`AnyDepth`]: struct.AnyDepth.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{AnyDepth, RelativePath};
+    ///
+    /// let is_rust = |s: &str| s.ends_with(".rs");
+    ///
+    /// let path = RelativePath::new("src/foo/bar/lib.rs");
+    ///
+    /// assert!(path.matches_pattern(&[&"src", &AnyDepth, &is_rust]));
+    /// assert!(!path.matches_pattern(&[&"src", &is_rust]));
+    /// assert!(!path.matches_pattern(&[&"tests", &AnyDepth]));
+    /// assert!(RelativePath::new("src/lib.rs").matches_pattern(&[&"src", &AnyDepth, &is_rust]));
+    /// ```
+    pub fn matches_pattern(&self, matchers: &[&dyn SegmentMatcher]) -> bool {
+        match_segments(matchers, self.iter())
+    }
+
+    /// Find the index of the first component equal to `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/target/debug/target");
+    ///
+    /// assert_eq!(Some(1), path.position_of_component("target"));
+    /// assert_eq!(Some(2), path.position_of_component("debug"));
+    /// assert_eq!(None, path.position_of_component("release"));
+    /// ```
+    pub fn position_of_component(&self, name: &str) -> Option<usize> {
+        self.iter().position(|c| c == name)
+    }
+
+    /// Strip the longest common prefix of `self` and `other`, returning what remains of each.
+    ///
+    /// Only considers whole path components to match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let a = RelativePath::new("a/b/c");
+    ///
+    /// assert_eq!(
+    ///     (RelativePath::new("c"), RelativePath::new("x/y")),
+    ///     a.strip_common("a/b/x/y".as_ref())
+    /// );
+    ///
+    /// assert_eq!(
+    ///     (RelativePath::new(""), RelativePath::new("")),
+    ///     a.strip_common("a//b/c/".as_ref())
+    /// );
+    /// ```
+    pub fn strip_common<'a>(
+        &'a self,
+        other: &'a RelativePath,
+    ) -> (&'a RelativePath, &'a RelativePath) {
+        let mut a = self.components();
+        let mut b = other.components();
+
+        loop {
+            let mut a_next = a.clone();
+            let mut b_next = b.clone();
+
+            match (a_next.next(), b_next.next()) {
+                (Some(x), Some(y)) if x == y => {
+                    a = a_next;
+                    b = b_next;
+                }
+                _ => return (a.as_relative_path(), b.as_relative_path()),
+            }
+        }
+    }
+
+    /// Check if the path consists of exactly one `.` component.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new(".").is_cur_dir());
+    /// assert!(RelativePath::new(".//").is_cur_dir());
+    /// assert!(!RelativePath::new("./.").is_cur_dir());
+    /// assert!(!RelativePath::new("").is_cur_dir());
+    /// ```
+    pub fn is_cur_dir(&self) -> bool {
+        let mut it = self.components();
+        it.next() == Some(Component::CurDir) && it.next().is_none()
+    }
+
+    /// Check if the path consists of exactly one `..` component.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("..").is_parent_dir());
+    /// assert!(RelativePath::new("../").is_parent_dir());
+    /// assert!(!RelativePath::new("../..").is_parent_dir());
+    /// assert!(!RelativePath::new(".").is_parent_dir());
+    /// ```
+    pub fn is_parent_dir(&self) -> bool {
+        let mut it = self.components();
+        it.next() == Some(Component::ParentDir) && it.next().is_none()
+    }
+
+    /// Feed the first `depth` components of the path into `state`.
+    ///
+    /// Paths which agree on their first `depth` components hash the same way, which makes this
+    /// useful to bucket paths by their leading directories. If the path has no more than `depth`
+    /// components, this is the same as hashing the whole path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// fn hash_prefix(path: &str, depth: usize) -> u64 {
+    ///     let mut state = DefaultHasher::new();
+    ///     RelativePath::new(path).hash_prefix(depth, &mut state);
+    ///     state.finish()
+    /// }
+    ///
+    /// assert_eq!(hash_prefix("foo/bar", 1), hash_prefix("foo//baz", 1));
+    /// assert_ne!(hash_prefix("foo/bar", 2), hash_prefix("foo//baz", 2));
+    ///
+    /// let mut state = DefaultHasher::new();
+    /// RelativePath::new("foo/bar").hash(&mut state);
+    /// assert_eq!(state.finish(), hash_prefix("foo/bar", 2));
+    /// ```
+    pub fn hash_prefix<H: Hasher>(&self, depth: usize, state: &mut H) {
+        for c in self.components().take(depth) {
+            c.hash(state);
+        }
+    }
+
+    /// Count the components of the path for which `f` returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new(".config/foo/.cache/bar");
+    ///
+    /// assert_eq!(2, path.count_components_matching(|c| c.starts_with('.')));
+    /// assert_eq!(1, path.count_components_matching(|c| c.len() > 6));
+    /// ```
+    pub fn count_components_matching<F>(&self, f: F) -> usize
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.iter().filter(|c| f(c)).count()
+    }
+
+    /// Check if the path ends with a separator.
+    ///
+    /// Trailing separators are ignored by [`components`] and comparisons, but can carry meaning in
+    /// some protocols, like marking a directory.
+    ///
+    /// [`components`]: #method.components
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("foo/bar/").has_trailing_separator());
+    /// assert!(!RelativePath::new("foo/bar").has_trailing_separator());
+    /// assert!(!RelativePath::new("").has_trailing_separator());
+    /// ```
+    pub fn has_trailing_separator(&self) -> bool {
+        self.inner.ends_with(SEP)
+    }
+
+    /// Check if any component of the path is contained in `names`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::collections::HashSet;
+    ///
+    /// let forbidden = [".git", ".ssh"].iter().copied().collect::<HashSet<_>>();
+    ///
+    /// assert!(RelativePath::new("foo/.git/config").intersects_components(&forbidden));
+    /// assert!(!RelativePath::new("foo/.github/config").intersects_components(&forbidden));
+    /// ```
+    pub fn intersects_components<S: BuildHasher>(&self, names: &HashSet<&str, S>) -> bool {
+        self.iter().any(|c| names.contains(c))
+    }
+
+    /// Returns the part of the path before the first component equal to `name`, or the whole
+    /// path if there is no such component.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/b/target/debug");
+    ///
+    /// assert_eq!(RelativePath::new("a/b"), path.truncate_before("target"));
+    /// assert_eq!(RelativePath::new(""), path.truncate_before("a"));
+    /// assert_eq!(path, path.truncate_before("release"));
+    /// ```
+    pub fn truncate_before(&self, name: &str) -> &RelativePath {
+        match self.split_at_component(|c| c == name) {
+            Some((prefix, _)) => prefix,
+            None => self,
+        }
+    }
+
+    /// Split the path at the first component for which `pred` returns `true`.
+    ///
+    /// Returns the part of the path before that component, and the part starting with it, or
+    /// [`None`] if no component matches.
+    ///
+    /// [`None`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("pkg/foo/1.2.0/lib/foo.so");
+    /// let is_version = |c: &str| c.starts_with(|c: char| c.is_ascii_digit());
+    ///
+    /// assert_eq!(
+    ///     Some((RelativePath::new("pkg/foo"), RelativePath::new("1.2.0/lib/foo.so"))),
+    ///     path.split_at_component(is_version)
+    /// );
+    ///
+    /// assert_eq!(None, path.split_at_component(|c| c == "bin"));
+    /// ```
+    pub fn split_at_component<F>(&self, pred: F) -> Option<(&RelativePath, &RelativePath)>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut it = self.components();
+
+        loop {
+            let rest = it.clone();
+
+            match it.next() {
+                Some(c) if pred(c.as_str()) => {
+                    return Some((self.prefix_before(&rest), rest.as_relative_path()))
+                }
+                Some(_) => (),
+                None => return None,
+            }
+        }
+    }
+
+    /// Call `f` with each component of the path, stopping at the first error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// fn validate(c: &str) -> Result<(), String> {
+    ///     if c.starts_with('.') {
+    ///         return Err(format!("hidden component `{}`", c));
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// assert_eq!(Ok(()), RelativePath::new("foo/bar").try_components(validate));
+    /// assert_eq!(
+    ///     Err(String::from("hidden component `.git`")),
+    ///     RelativePath::new("foo/.git/config").try_components(validate)
+    /// );
+    /// ```
+    pub fn try_components<F, E>(&self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&str) -> Result<(), E>,
+    {
+        self.iter().try_for_each(f)
+    }
+
+    /// Keep at most `max` components from the end of the path, prepending a
+    /// `..` marker if any components were dropped.
+    ///
+    /// This is useful for displaying paths in a limited amount of space while
+    /// keeping the file name, and still getting a path which can be further
+    /// manipulated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/b/c/d/e");
+    ///
+    /// assert_eq!("../d/e", path.cap_components(2));
+    /// assert_eq!("..", path.cap_components(0));
+    /// assert_eq!("a/b/c/d/e", path.cap_components(5));
+    /// ```
+    pub fn cap_components(&self, max: usize) -> RelativePathBuf {
+        let count = self.components().count();
+
+        if count <= max {
+            return self.to_relative_path_buf();
+        }
+
+        let mut rest = self.components();
+
+        for _ in 0..count - max {
+            rest.next();
+        }
+
+        RelativePath::new(PARENT_STR).join(rest.as_relative_path())
+    }
+
+    /// Test if two paths are equal after being [normalized].
+    ///
+    /// Unlike `==` which only ignores redundant separators, this also takes
+    /// `.` and `..` components into account. No allocation happens if both
+    /// paths are already normalized.
+    ///
+    /// [normalized]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert!(RelativePath::new("a/b").eq_normalized("a/./b"));
+    /// assert!(RelativePath::new("a/c").eq_normalized("a/b/../c"));
+    /// assert!(!RelativePath::new("a/b").eq_normalized("a/b/.."));
+    /// ```
+    pub fn eq_normalized<P>(&self, other: P) -> bool
+    where
+        P: AsRef<RelativePath>,
+    {
+        let other = other.as_ref();
+
+        match (self.is_normalized(), other.is_normalized()) {
+            (true, true) => self == other,
+            (true, false) => self == other.normalize(),
+            (false, true) => self.normalize() == other,
+            (false, false) => self.normalize() == other.normalize(),
+        }
+    }
+
+    /// Join an untrusted path onto `self` such that the result can never
+    /// escape `self`.
+    ///
+    /// Components of `untrusted` are applied lexically: `.` is ignored, `..`
+    /// removes a previously joined component and is dropped if there is
+    /// nothing to remove, and a leading separator is ignored. Only components
+    /// of `untrusted` are ever removed, never those of `self`.
+    ///
+    /// Note that this is purely lexical and does not account for symbolic
+    /// links on the filesystem.
+    ///
+    /// The guarantee only holds for relative paths separated by `/`. A
+    /// component like `..\x` or `C:` is kept as a normal component, but can
+    /// still escape or re-root the path once it is converted into a platform
+    /// path with [`to_path`] on Windows. Use [`has_forbidden_chars`] to reject
+    /// such input first if that matters.
+    ///
+    /// [`to_path`]: #method.to_path
+    /// [`has_forbidden_chars`]: #method.has_forbidden_chars
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let uploads = RelativePath::new("uploads");
+    ///
+    /// assert_eq!("uploads/etc/passwd", uploads.join_sanitized("../../etc/passwd"));
+    /// assert_eq!("uploads/b", uploads.join_sanitized("a/../b"));
+    /// assert_eq!("uploads/etc", uploads.join_sanitized("/etc"));
+    /// assert_eq!("uploads", uploads.join_sanitized("a/../.."));
+    /// ```
+    pub fn join_sanitized<P>(&self, untrusted: P) -> RelativePathBuf
+    where
+        P: AsRef<RelativePath>,
+    {
+        let mut buf = self.to_relative_path_buf();
+        let mut depth = 0usize;
+
+        for c in untrusted.as_ref().components() {
+            match c {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    if depth > 0 {
+                        buf.pop();
+                        depth -= 1;
+                    }
+                }
+                Component::Normal(name) => {
+                    buf.push(name);
+                    depth += 1;
+                }
+            }
+        }
+
+        buf
+    }
+
+    /// Construct a path with the components of `self` in reverse order.
+    ///
+    /// Redundant separators, including leading and trailing ones, are not
+    /// preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!("c/b/a", RelativePath::new("a/b/c").reversed());
+    /// assert_eq!("c/b/a", RelativePath::new("/a//b/c/").reversed());
+    /// assert_eq!("", RelativePath::new("").reversed());
+    /// ```
+    pub fn reversed(&self) -> RelativePathBuf {
+        let mut buf = RelativePathBuf::new();
+
+        for c in self.iter().rev() {
+            buf.push(c);
+        }
+
+        buf
+    }
+
+    /// Calculate the length in bytes of the native path which would be
+    /// produced by [normalizing] this path and [joining it onto] `base`.
+    ///
+    /// This is equivalent to `self.normalize().to_path(base)` followed by
+    /// taking the length of its encoded bytes, but doesn't allocate. It can be
+    /// used to reject paths which would be too long before materializing them.
+    ///
+    /// [normalizing]: #method.normalize
+    /// [joining it onto]: #method.to_path
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::path::Path;
+    ///
+    /// let path = RelativePath::new("foo/./bar/../baz");
+    /// let base = Path::new("base");
+    ///
+    /// assert_eq!(path.normalize().to_path(base).as_os_str().len(), path.resolved_len(base));
+    /// assert_eq!(8, RelativePath::new("foo/../bar").resolved_len(Path::new("base")));
+    /// ```
+    pub fn resolved_len<P>(&self, base: P) -> usize
+    where
+        P: AsRef<path::Path>,
+    {
+        use self::Component::*;
+
+        let base = base.as_ref().as_os_str();
+
+        let mut len = 0;
+        let mut parts = 0;
+        let mut skip = 0;
+
+        for c in self.components().rev() {
+            match c {
+                CurDir => (),
+                ParentDir => skip += 1,
+                Normal(_) if skip > 0 => skip -= 1,
+                Normal(name) => {
+                    len += name.len();
+                    parts += 1;
+                }
+            }
+        }
+
+        len += skip * PARENT_STR.len();
+        parts += skip;
+
+        if parts == 0 {
+            return base.len();
+        }
+
+        let needs_sep = match base.as_encoded_bytes().last() {
+            Some(&b) => !path::is_separator(char::from(b)),
+            None => false,
+        };
+
+        base.len() + len + parts - 1 + usize::from(needs_sep)
+    }
+
+    /// Get a string key which is identical for all paths that are equal once
+    /// [normalized].
+    ///
+    /// This is the string form of the normalized path, and is suitable for
+    /// deduplicating paths through for example a `HashSet<String>`.
+    ///
+    /// [normalized]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    /// use std::collections::HashSet;
+    ///
+    /// let mut seen = HashSet::new();
+    ///
+    /// assert!(seen.insert(RelativePath::new("a/b").canonical_key()));
+    /// assert!(!seen.insert(RelativePath::new("a//b").canonical_key()));
+    /// assert!(!seen.insert(RelativePath::new("a/./b").canonical_key()));
+    /// assert!(!seen.insert(RelativePath::new("a/c/../b/").canonical_key()));
+    /// ```
+    pub fn canonical_key(&self) -> String {
+        self.normalize().inner
+    }
+
+    /// Iterate over the normal components of the path, skipping any `.` and
+    /// `..` components.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("./foo/../bar//baz/.");
+    /// let names = path.normal_components().collect::<Vec<_>>();
+    /// assert_eq!(vec!["foo", "bar", "baz"], names);
+    /// ```
+    pub fn normal_components(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.components().filter_map(|c| match c {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+    }
+
+    /// Strip a literal string prefix from the path, but only if the prefix
+    /// ends at a component boundary.
+    ///
+    /// Unlike [`strip_prefix`] this doesn't compare by components, so
+    /// separators in `prefix` must match the path exactly. Any separators
+    /// following the prefix are removed from the result.
+    ///
+    /// [`strip_prefix`]: #method.strip_prefix
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("abc/def");
+    /// assert_eq!(Some(RelativePath::new("def")), path.strip_str_prefix("abc"));
+    /// assert_eq!(Some(RelativePath::new("def")), path.strip_str_prefix("abc/"));
+    /// assert_eq!(Some(RelativePath::new("")), path.strip_str_prefix("abc/def"));
+    /// assert_eq!(None, RelativePath::new("abcd/ef").strip_str_prefix("abc"));
+    /// ```
+    pub fn strip_str_prefix(&self, prefix: &str) -> Option<&RelativePath> {
+        let rest = self.inner.strip_prefix(prefix)?;
+
+        if prefix.is_empty() || prefix.ends_with(SEP) || rest.is_empty() || rest.starts_with(SEP) {
+            return Some(RelativePath::new(rest.trim_start_matches(SEP)));
+        }
+
+        None
+    }
+
+    /// Assert in debug builds that the path doesn't start with a separator.
+    ///
+    /// A leading separator is ignored by most operations in this crate, so
+    /// its presence usually means an absolute path was converted by mistake.
+    /// This does nothing in release builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if debug assertions are enabled and the path starts with a
+    /// separator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// RelativePath::new("foo/bar").debug_assert_relative();
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// use relative_path::RelativePath;
+    ///
+    /// # if !cfg!(debug_assertions) { panic!() }
+    /// RelativePath::new("/foo/bar").debug_assert_relative();
+    /// ```
+    #[inline]
+    pub fn debug_assert_relative(&self) {
+        debug_assert!(
+            !self.starts_with_sep(),
+            "path {:?} starts with a separator",
+            self
+        );
+    }
+
+    /// Build an owned path where the extension is replaced by the result of
+    /// calling `f` with the current extension.
+    ///
+    /// Returning [`None`] or an empty string from `f` strips the extension. If
+    /// the path has no file name, it is returned unchanged and `f` is not
+    /// called.
+    ///
+    /// [`None`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("static/app.js");
+    ///
+    /// let upper = path.map_extension(|ext| ext.map(str::to_uppercase));
+    /// assert_eq!("static/app.JS", upper);
+    ///
+    /// let min = path.map_extension(|ext| ext.map(|ext| format!("min.{}", ext)));
+    /// assert_eq!("static/app.min.js", min);
+    ///
+    /// assert_eq!("static/app", path.map_extension(|_| None));
+    /// assert_eq!("README.md", RelativePath::new("README").map_extension(|_| Some("md".into())));
+    /// ```
+    pub fn map_extension<F>(&self, f: F) -> RelativePathBuf
+    where
+        F: FnOnce(Option<&str>) -> Option<String>,
+    {
+        let mut buf = self.to_relative_path_buf();
+
+        if self.file_name().is_some() {
+            let extension = f(self.extension());
+            buf.set_extension(extension.as_deref().unwrap_or_default());
+        }
+
+        buf
+    }
+
+    /// Get the length in bytes of the string this path is serialized as.
+    ///
+    /// Paths are serialized as-is without being normalized, so this is the
+    /// length of the underlying string. Any quoting or escaping added by the
+    /// serialization format is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(9, RelativePath::new("foo//bar/").serialized_len());
+    /// ```
+    pub fn serialized_len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Find the index of the first of `roots` which is a prefix of `self`.
+    ///
+    /// Roots are compared by components, like in [`starts_with`].
+    ///
+    /// [`starts_with`]: #method.starts_with
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let roots = ["api", "static"];
+    ///
+    /// assert_eq!(Some(1), RelativePath::new("static/img/x.png").subpath_of_which(&roots));
+    /// assert_eq!(Some(0), RelativePath::new("api").subpath_of_which(&roots));
+    /// assert_eq!(None, RelativePath::new("apis/v1").subpath_of_which(&roots));
+    /// ```
+    pub fn subpath_of_which<P>(&self, roots: &[P]) -> Option<usize>
+    where
+        P: AsRef<RelativePath>,
+    {
+        roots.iter().position(|root| self.starts_with(root))
+    }
+
+    /// Like [`strip_prefix`], but returns `.` instead of an empty path if
+    /// `self` is equal to `base`.
+    ///
+    /// Returns [`None`] if `base` is not a prefix of `self`.
+    ///
+    /// [`strip_prefix`]: #method.strip_prefix
+    /// [`None`]: std::option::Option
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("src/lib.rs");
+    ///
+    /// let rest = path.strip_prefix_or_dot("src");
+    /// assert_eq!(Some(RelativePath::new("lib.rs")), rest.as_deref());
+    ///
+    /// let rest = path.strip_prefix_or_dot("src/lib.rs/");
+    /// assert_eq!(Some(RelativePath::new(".")), rest.as_deref());
+    ///
+    /// assert_eq!(None, path.strip_prefix_or_dot("tests"));
+    /// ```
+    pub fn strip_prefix_or_dot<P>(&self, base: P) -> Option<Cow<'_, RelativePath>>
+    where
+        P: AsRef<RelativePath>,
+    {
+        let rest = iter_after_by(self.components(), base.as_ref().components(), |a, b| a == b)?;
+
+        if rest.clone().next().is_none() {
+            return Some(Cow::Owned(RelativePathBuf::from(CURRENT_STR)));
+        }
+
+        Some(Cow::Borrowed(rest.as_relative_path()))
+    }
+
+    /// Split the file name into its stem and all of its extensions.
+    ///
+    /// A leading `.` is considered part of the stem, so dotfiles like `.env`
+    /// have no extensions. Returns [`None`] if there is no [file name].
+    ///
+    /// [`None`]: std::option::Option
+    /// [file name]: #method.file_name
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let parts = RelativePath::new("a/backup.tar.gz").file_name_parts().unwrap();
+    /// assert_eq!("backup", parts.stem);
+    /// assert_eq!(vec!["tar", "gz"], parts.extensions);
+    ///
+    /// let parts = RelativePath::new(".env").file_name_parts().unwrap();
+    /// assert_eq!(".env", parts.stem);
+    /// assert!(parts.extensions.is_empty());
+    ///
+    /// assert_eq!(None, RelativePath::new("a/..").file_name_parts());
+    /// ```
+    pub fn file_name_parts(&self) -> Option<FileNameParts<'_>> {
+        let name = self.file_name()?;
+        let skip = usize::from(name.starts_with(STEM_SEP));
+
+        let (stem, extensions) = match name[skip..].find(STEM_SEP) {
+            Some(n) => (
+                &name[..skip + n],
+                name[skip + n + 1..].split(STEM_SEP).collect(),
+            ),
+            None => (name, Vec::new()),
+        };
+
+        Some(FileNameParts { stem, extensions })
+    }
+
+    /// Iterate over the components of the path, percent-decoding each of
+    /// them.
+    ///
+    /// Components are decoded lazily, and are only allocated if they contain
+    /// an escape. An encoded separator (`%2F`) is treated as an error rather
+    /// than as a separator. Note that decoded components like `%2E%2E` are
+    /// not treated as `..`.
+    ///
+    /// # Errors
+    ///
+    /// Each item is an error with the corresponding [`DecodeErrorKind`] if the
+    /// component contains an invalid escape, an encoded separator, or decodes
+    /// into invalid UTF-8.
+    ///
+    /// [`DecodeErrorKind`]: enum.DecodeErrorKind.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::{DecodeErrorKind, RelativePath};
+    ///
+    /// let path = RelativePath::new("docs/hello%20world/caf%C3%A9");
+    /// let decoded = path.decoded_components().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(vec!["docs", "hello world", "café"], decoded);
+    ///
+    /// let path = RelativePath::new("a%2Fb/c%zz");
+    /// let mut it = path.decoded_components();
+    /// assert_eq!(Some(Err(DecodeErrorKind::EncodedSeparator.into())), it.next());
+    /// assert_eq!(Some(Err(DecodeErrorKind::InvalidEscape.into())), it.next());
+    /// # Ok::<_, relative_path::DecodeError>(())
+    /// ```
+    pub fn decoded_components(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Result<Cow<'_, str>, DecodeError>> {
+        self.iter().map(percent_decode)
+    }
+
+    /// Test if `self` and `other` have the same first component.
+    ///
+    /// Returns `false` if either path has no components.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("src/lib.rs");
+    ///
+    /// assert!(path.same_first_component("/src//main.rs"));
+    /// assert!(!path.same_first_component("tests/lib.rs"));
+    /// assert!(!RelativePath::new("").same_first_component(""));
+    /// ```
+    pub fn same_first_component<P>(&self, other: P) -> bool
+    where
+        P: AsRef<RelativePath>,
+    {
+        match (self.components().next(), other.as_ref().components().next()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Count the number of components in the path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// assert_eq!(3, RelativePath::new("foo///bar/baz").depth());
+    /// assert_eq!(2, RelativePath::new("./..").depth());
+    /// assert_eq!(0, RelativePath::new("").depth());
+    /// assert_eq!(0, RelativePath::new("//").depth());
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.components().count()
+    }
+
+    /// Test if the length of the path in bytes is at most `max_total_bytes`,
+    /// and the length of each of its components is at most
+    /// `max_component_bytes`.
+    ///
+    /// The total length is that of the underlying string, including any
+    /// redundant separators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("foo/barbaz");
+    ///
+    /// assert!(path.within_limits(10, 6));
+    /// assert!(!path.within_limits(9, 6));
+    /// assert!(!path.within_limits(10, 5));
+    /// ```
+    pub fn within_limits(&self, max_total_bytes: usize, max_component_bytes: usize) -> bool {
+        self.inner.len() <= max_total_bytes && self.iter().all(|c| c.len() <= max_component_bytes)
+    }
+
+    /// Compute the path which, when joined onto `base`, reaches `self`.
+    ///
+    /// Both paths are [normalized] first. A `..` is emitted for every
+    /// component of `base` following the components they have in common,
+    /// followed by the remaining components of `self`. The result is empty if
+    /// both paths are equal.
+    ///
+    /// Since this is lexical, the result is only meaningful if `base` has no
+    /// leading `..` components which are not shared with `self`, because the
+    /// names of the directories they refer to are not known.
+    ///
+    /// [normalized]: #method.normalize
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let path = RelativePath::new("a/b/c");
+    ///
+    /// assert_eq!("../b/c", path.relative_to("a/x"));
+    /// assert_eq!("c", path.relative_to("a/./b/"));
+    /// assert_eq!("../..", RelativePath::new("a").relative_to("a/b/c").as_str());
+    /// assert_eq!("", path.relative_to("a/x/../b/c"));
+    ///
+    /// let base = RelativePath::new("a/x");
+    /// assert_eq!(path.normalize(), base.join_normalized(path.relative_to(base)));
+    /// ```
+    pub fn relative_to<P>(&self, base: P) -> RelativePathBuf
+    where
+        P: AsRef<RelativePath>,
+    {
+        let path = self.normalize();
+        let base = base.as_ref().normalize();
+
+        let mut path_components = path.components();
+        let mut base_components = base.components();
+
+        loop {
+            let (p, b) = (path_components.clone(), base_components.clone());
+
+            match (path_components.next(), base_components.next()) {
+                (Some(a), Some(b)) if a == b => (),
+                _ => {
+                    path_components = p;
+                    base_components = b;
+                    break;
+                }
+            }
+        }
+
+        let mut buf = RelativePathBuf::new();
+
+        for _ in base_components {
+            buf.push(PARENT_STR);
+        }
+
+        let rest = path_components.as_relative_path();
+
+        if !rest.is_empty() {
+            buf.push(rest);
+        }
+
+        buf
+    }
+
+    /// Collect the components of the path into an exactly sized boxed slice.
+    ///
+    /// Unlike collecting into a [`Vec`], this doesn't keep any spare capacity
+    /// around.
+    ///
+    /// [`Vec`]: std::vec::Vec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use relative_path::RelativePath;
+    ///
+    /// let components = RelativePath::new("foo//bar/./baz").into_component_box();
+    /// assert_eq!(&["foo", "bar", ".", "baz"][..], &components[..]);
     /// ```
-    pub fn join_normalized<P: AsRef<RelativePath>>(&self, path: P) -> RelativePathBuf {
-        let mut stack = Vec::new();
-        relative_traversal(&mut stack, self.components());
-        relative_traversal(&mut stack, path.as_ref().components());
-        let string = stack
-            .into_iter()
-            .map(|c| c.as_str())
-            .collect::<Vec<_>>()
-            .join("/");
-        RelativePathBuf::from(string)
+    pub fn into_component_box(&self) -> Box<[String]> {
+        self.iter().map(String::from).collect()
     }
 
-    /// Return an owned `RelativePathBuf`, with all non-normal components moved to the beginning of
-    /// the path.
-    ///
-    /// This permits for a normalized representation of different relative components.
-    ///
-    /// Normalization is a _destructive_ operation if the path references an actual filesystem
-    /// path.
-    /// An example of this is symlinks under unix, a path like `foo/../bar` might reference a
-    /// different location other than `./bar`.
+    /// Pair each component of the path with the path leading up to and
+    /// including it.
     ///
-    /// Normalization is a logical operation that is only valid if the relative path is part of
-    /// some context which doesn't have semantics that causes it to break, like symbolic links.
+    /// This is useful for rendering breadcrumbs, where each component links
+    /// to its own sub-path.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use relative_path::RelativePath;
     ///
+    /// let crumbs = RelativePath::new("a//b/c/").breadcrumbs();
+    ///
     /// assert_eq!(
-    ///     RelativePath::new("../foo/baz.txt"),
-    ///     RelativePath::new("../foo/./bar/../baz.txt").normalize().as_relative_path()
+    ///     vec![("a", "a".into()), ("b", "a/b".into()), ("c", "a/b/c".into())],
+    ///     crumbs
     /// );
     /// ```
-    pub fn normalize(&self) -> RelativePathBuf {
-        let mut stack = Vec::new();
-        relative_traversal(&mut stack, self.components());
-        let string = stack
-            .into_iter()
-            .map(|c| c.as_str())
-            .collect::<Vec<_>>()
-            .join("/");
-        RelativePathBuf::from(string)
+    pub fn breadcrumbs(&self) -> Vec<(&str, RelativePathBuf)> {
+        let mut current = RelativePathBuf::new();
+        let mut out = Vec::new();
+
+        for c in self.iter() {
+            current.push(c);
+            out.push((c, current.clone()));
+        }
+
+        out
     }
 
     /// Check if path starts with a path separator.
@@ -1101,10 +3986,81 @@ impl RelativePath {
         self.inner.starts_with(SEP)
     }
 
-    /// Check if path ends with a path separator.
-    fn ends_with_sep(&self) -> bool {
-        self.inner.ends_with(SEP)
+    /// The part of the path before the components remaining in `rest`, which must only have been
+    /// advanced from the front.
+    fn prefix_before(&self, rest: &Components<'_>) -> &RelativePath {
+        let end = self.inner.len() - rest.source.len();
+        RelativePath::new(self.inner[..end].trim_end_matches(SEP))
+    }
+
+    /// Byte offset of `slice` in the path, which must be a sub-slice of it.
+    fn offset_of(&self, slice: &str) -> usize {
+        slice.as_ptr() as usize - self.inner.as_ptr() as usize
+    }
+
+    /// Strip a single leading path separator, if present.
+    fn trim_sep_once(&self) -> &str {
+        if self.starts_with_sep() {
+            &self.inner[1..]
+        } else {
+            &self.inner
+        }
+    }
+}
+
+/// Find the longest common prefix of all the given paths.
+///
+/// Only considers whole path components to match. Returns [`None`] if `paths` is empty.
+///
+/// This runs in time linear to the total number of components, and only allocates once for the
+/// result.
+///
+/// [`None`]: std::option::Option
+///
+/// # Examples
+///
+/// ```rust
+/// use relative_path::{common_base, RelativePath};
+///
+/// let base = common_base(&["foo/bar/baz", "foo/bar//biz", "foo/bar/baz/buz"]);
+/// assert_eq!(Some("foo/bar".into()), base);
+///
+/// let base = common_base(&["foo/bar", "baz"]);
+/// assert_eq!(Some("".into()), base);
+///
+/// assert_eq!(None, common_base(Vec::<&RelativePath>::new()));
+/// ```
+pub fn common_base<I, P>(paths: I) -> Option<RelativePathBuf>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<RelativePath>,
+{
+    let mut paths = paths.into_iter();
+    let first = paths.next()?;
+    let first = first.as_ref();
+
+    let mut common = first.components().count();
+
+    for path in paths {
+        if common == 0 {
+            break;
+        }
+
+        common = first
+            .components()
+            .zip(path.as_ref().components())
+            .take(common)
+            .take_while(|(a, b)| a == b)
+            .count();
     }
+
+    let mut rest = first.components();
+
+    for _ in 0..common {
+        rest.next();
+    }
+
+    Some(first.prefix_before(&rest).to_relative_path_buf())
 }
 
 impl ToOwned for RelativePath {
@@ -1155,7 +4111,7 @@ impl cmp::Eq for RelativePath {}
 
 impl cmp::PartialOrd for RelativePath {
     fn partial_cmp(&self, other: &RelativePath) -> Option<cmp::Ordering> {
-        self.components().partial_cmp(other.components())
+        Some(self.cmp(other))
     }
 }
 
@@ -1210,24 +4166,24 @@ impl<'a> fmt::Display for Display<'a> {
 }
 
 #[cfg(feature = "serde")]
-impl serde::ser::Serialize for RelativePathBuf {
+impl ::serde::ser::Serialize for RelativePathBuf {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: serde::ser::Serializer,
+        S: ::serde::ser::Serializer,
     {
         serializer.serialize_str(&self.inner)
     }
 }
 
 #[cfg(feature = "serde")]
-impl<'de> serde::de::Deserialize<'de> for RelativePathBuf {
+impl<'de> ::serde::de::Deserialize<'de> for RelativePathBuf {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        D: serde::de::Deserializer<'de>,
+        D: ::serde::de::Deserializer<'de>,
     {
         struct RelativePathBufVisitor;
 
-        impl<'de> serde::de::Visitor<'de> for RelativePathBufVisitor {
+        impl<'de> ::serde::de::Visitor<'de> for RelativePathBufVisitor {
             type Value = RelativePathBuf;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -1236,14 +4192,14 @@ impl<'de> serde::de::Deserialize<'de> for RelativePathBuf {
 
             fn visit_string<E>(self, input: String) -> Result<Self::Value, E>
             where
-                E: serde::de::Error,
+                E: ::serde::de::Error,
             {
                 Ok(RelativePathBuf::from(input))
             }
 
             fn visit_str<E>(self, input: &str) -> Result<Self::Value, E>
             where
-                E: serde::de::Error,
+                E: ::serde::de::Error,
             {
                 Ok(RelativePathBuf::from(input.to_string()))
             }
@@ -1254,15 +4210,126 @@ impl<'de> serde::de::Deserialize<'de> for RelativePathBuf {
 }
 
 #[cfg(feature = "serde")]
-impl serde::ser::Serialize for RelativePath {
+impl<'de> ::serde::de::Deserialize<'de> for Box<RelativePath> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        RelativePathBuf::deserialize(deserializer).map(RelativePathBuf::into_boxed_relative_path)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de: 'a, 'a> ::serde::de::Deserialize<'de> for &'a RelativePath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        struct RelativePathVisitor;
+
+        impl<'a> ::serde::de::Visitor<'a> for RelativePathVisitor {
+            type Value = &'a RelativePath;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a borrowed relative path")
+            }
+
+            fn visit_borrowed_str<E>(self, input: &'a str) -> Result<Self::Value, E>
+            where
+                E: ::serde::de::Error,
+            {
+                Ok(RelativePath::new(input))
+            }
+
+            fn visit_borrowed_bytes<E>(self, input: &'a [u8]) -> Result<Self::Value, E>
+            where
+                E: ::serde::de::Error,
+            {
+                RelativePath::from_utf8(input).map_err(|_| {
+                    ::serde::de::Error::invalid_value(::serde::de::Unexpected::Bytes(input), &self)
+                })
+            }
+        }
+
+        deserializer.deserialize_str(RelativePathVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::ser::Serialize for RelativePath {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: serde::ser::Serializer,
+        S: ::serde::ser::Serializer,
     {
         serializer.serialize_str(&self.inner)
     }
 }
 
+/// Helpers for using alternative serialized forms of relative paths with serde, through
+/// `#[serde(deserialize_with = "...")]`.
+///
+/// This requires the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod serde {
+    use super::RelativePathBuf;
+    use ::serde::de::{self, Unexpected};
+    use std::fmt;
+
+    /// Deserialize a [`RelativePathBuf`] from a sequence of components, like `["a", "b", "c"]`.
+    ///
+    /// Each component must be non-empty, and must not contain a path separator.
+    ///
+    /// [`RelativePathBuf`]: ../struct.RelativePathBuf.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::de::{value, IntoDeserializer};
+    ///
+    /// let deserializer: value::SeqDeserializer<_, value::Error> =
+    ///     vec!["a", "b", "c"].into_deserializer();
+    ///
+    /// let path = relative_path::serde::from_components(deserializer);
+    /// assert_eq!(Ok("a/b/c".into()), path);
+    /// ```
+    pub fn from_components<'de, D>(deserializer: D) -> Result<RelativePathBuf, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct ComponentsVisitor;
+
+        impl<'de> de::Visitor<'de> for ComponentsVisitor {
+            type Value = RelativePathBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence of path components")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut buf = RelativePathBuf::new();
+
+                while let Some(component) = seq.next_element::<String>()? {
+                    if component.is_empty() || component.contains(super::SEP) {
+                        return Err(de::Error::invalid_value(
+                            Unexpected::Str(&component),
+                            &"a non-empty path component without separators",
+                        ));
+                    }
+
+                    buf.push(component);
+                }
+
+                Ok(buf)
+            }
+        }
+
+        deserializer.deserialize_seq(ComponentsVisitor)
+    }
+}
+
 macro_rules! impl_cmp {
     ($lhs:ty, $rhs:ty) => {
         impl<'a, 'b> PartialEq<$rhs> for $lhs {
@@ -1644,6 +4711,15 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_file_name_trailing_slash() {
+        t!("a/", parent: Some(""), file_name: Some("a"));
+        t!("a//", parent: Some(""), file_name: Some("a"));
+        t!("a/b/", parent: Some("a"), file_name: Some("b"));
+        t!("a/b//", parent: Some("a"), file_name: Some("b"));
+        t!("a/b/../", parent: Some("a/b"), file_name: None);
+    }
+
     #[test]
     pub fn test_stem_ext() {
         t!("foo",
@@ -1735,6 +4811,8 @@ mod tests {
         tse!("..", "foo", "..", false);
         tse!("foo/..", "bar", "foo/..", false);
         tse!("/", "foo", "/", false);
+        tse!("a//foo.rs/.", "o", "a//foo.o", true);
+        tse!("a//foo.rs", "o", "a//foo.o", true);
     }
 
     #[test]
@@ -1862,6 +4940,28 @@ mod tests {
         ends_with: false,
         relative_from: None
         );
+
+        tc!("foo///bar/", "/foo/bar",
+        eq: true,
+        starts_with: true,
+        ends_with: true,
+        relative_from: Some("")
+        );
+
+        let owned = RelativePathBuf::from("foo///bar");
+        assert_eq!(hash(RelativePath::new("foo/bar")), hash(&owned));
+    }
+
+    #[test]
+    fn test_hash_map_borrow() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(RelativePathBuf::from("foo/bar"), 42);
+
+        assert_eq!(Some(&42), map.get(rp("foo/bar")));
+        assert_eq!(Some(&42), map.get(rp("/foo//bar/")));
+        assert_eq!(None, map.get(rp("foo/bar/baz")));
     }
 
     #[test]
@@ -1872,6 +4972,12 @@ mod tests {
             &rp("hello/world").join("///foo/bar/baz"),
         );
         assert_components(&["foo", "bar", "baz"], &rp("").join("foo/bar/baz"));
+        assert_eq!("foo/bar", rp("").join("/foo/bar").as_str());
+        assert_eq!("foo/bar", rp("foo").join("/bar").as_str());
+        // Like `push`, joining an empty path adds a trailing separator, the same as
+        // `std::path::Path::join`.
+        assert_eq!("foo/", rp("foo").join("").as_str());
+        assert_eq!("", rp("").join("").as_str());
     }
 
     #[test]
@@ -1892,6 +4998,18 @@ mod tests {
         assert_eq!(expected, path_buf);
     }
 
+    #[test]
+    fn test_to_logical_path() {
+        let base = Path::new("root").join("dir");
+
+        assert_eq!(base.join("b"), rp("a/../b").to_logical_path(&base));
+        assert_eq!(base.join("a"), rp("./a/.//").to_logical_path(&base));
+        assert_eq!(Path::new("root"), rp("..").to_logical_path(&base));
+        assert_eq!(Path::new(""), rp("../..").to_logical_path(&base));
+        assert_eq!(Path::new(".."), rp("../../..").to_logical_path(&base));
+        assert_eq!(Path::new("/"), rp("../..").to_logical_path("/"));
+    }
+
     #[test]
     fn test_eq() {
         assert_eq!(rp("//foo///bar"), rp("/foo/bar"));
@@ -1946,6 +5064,69 @@ mod tests {
     #[test]
     fn test_normalize() {
         assert_eq!(rp("c/d"), rp("a/.././b/../c/d").normalize());
+        assert_eq!("a/c", rp("a/b/../c").normalize().as_str());
+        assert_eq!("../../x", rp("../../x").normalize().as_str());
+        assert_eq!("a/b", rp("a/./b/").normalize().as_str());
+        assert_eq!("..", rp("a/../..").normalize().as_str());
+    }
+
+    #[test]
+    fn test_resolve_dots_trailing_separator() {
+        assert_eq!("a", rp("a/b/..").resolve_dots().as_str());
+        assert_eq!("a", rp("a/.").resolve_dots().as_str());
+        assert_eq!("x", rp("x/a/..").resolve_dots().as_str());
+        assert_eq!("x//", rp("x//a/../").resolve_dots().as_str());
+        assert_eq!("/", rp("/a/..").resolve_dots().as_str());
+        assert_eq!("", rp("a/..").resolve_dots().as_str());
+    }
+
+    #[test]
+    fn test_normalize_idempotent() {
+        const SEGMENTS: &[&str] = &["a", "b", ".", "..", ""];
+
+        let mut paths = vec![String::new()];
+        let mut last = vec![String::new()];
+
+        for _ in 0..4 {
+            let mut next = Vec::new();
+
+            for path in &last {
+                for segment in SEGMENTS {
+                    next.push(format!("{}/{}", path, segment));
+                    next.push(format!("{}{}", path, segment));
+                }
+            }
+
+            paths.extend(next.iter().cloned());
+            last = next;
+        }
+
+        for path in &paths {
+            let path = rp(path);
+            let normalized = path.normalize();
+
+            assert!(
+                normalized.is_normalized(),
+                "{:?}.normalize() = {:?} is not normalized",
+                path,
+                normalized
+            );
+
+            assert_eq!(
+                normalized.as_str(),
+                normalized.normalize().as_str(),
+                "normalizing {:?} is not idempotent",
+                path
+            );
+
+            assert_eq!(
+                path.is_normalized(),
+                path.as_str() == normalized.as_str(),
+                "{:?}.is_normalized() disagrees with {:?}.normalize()",
+                path,
+                path
+            );
+        }
     }
 
     #[test]
@@ -1961,6 +5142,259 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_joined_relative_of() {
+        assert_eq!(
+            Path::new("..").join("x"),
+            rp("a").joined_relative_of("../../x", "")
+        );
+        assert_eq!(
+            Path::new("root").join("a").join("b"),
+            rp("a/.").joined_relative_of("./b", "root")
+        );
+        assert_eq!(
+            Path::new("..").join("..").join("x"),
+            rp("").joined_relative_of("../x", "..")
+        );
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        let hidden = |s: &str| s.starts_with('.');
+
+        assert!(rp("").matches_pattern(&[]));
+        assert!(rp("").matches_pattern(&[&AnyDepth]));
+        assert!(!rp("a").matches_pattern(&[]));
+        assert!(rp("a/b/c").matches_pattern(&[&AnyDepth]));
+        assert!(rp("a/b/c").matches_pattern(&[&AnyDepth, &"c"]));
+        assert!(rp("a/b/c").matches_pattern(&[&"a", &AnyDepth, &AnyDepth, &"c"]));
+        assert!(!rp("a/b/c").matches_pattern(&[&AnyDepth, &"b"]));
+        assert!(rp("a/.b/c").matches_pattern(&[&"a", &hidden, &"c"]));
+        assert!(!rp("a/b/c").matches_pattern(&[&"a", &hidden, &"c"]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_from_components() {
+        use ::serde::de::{value, IntoDeserializer};
+
+        fn from_components(components: Vec<&str>) -> Result<RelativePathBuf, value::Error> {
+            let deserializer: value::SeqDeserializer<_, value::Error> =
+                components.into_deserializer();
+            crate::serde::from_components(deserializer)
+        }
+
+        assert_eq!(Ok(RelativePathBuf::new()), from_components(vec![]));
+        assert_eq!(Ok(rp("a/b").to_owned()), from_components(vec!["a", "b"]));
+        assert!(from_components(vec!["a", ""]).is_err());
+        assert!(from_components(vec!["a/b", "c"]).is_err());
+    }
+
+    #[test]
+    fn test_join_sanitized() {
+        assert_eq!("a/b", rp("a/b").join_sanitized(""));
+        assert_eq!("a/b", rp("a/b").join_sanitized(".."));
+        assert_eq!("a/b", rp("a/b").join_sanitized("./../."));
+        assert_eq!("a/b/c", rp("a/b").join_sanitized("//c"));
+        assert_eq!("a/b/d", rp("a/b").join_sanitized("c/../../d"));
+        assert_eq!("a/b/c/e", rp("a/b").join_sanitized("c/d/../e"));
+        assert_eq!("a/b/..", rp("a/b/..").join_sanitized("c/.."));
+        assert_eq!("c", rp("").join_sanitized("../c"));
+    }
+
+    #[test]
+    fn test_reversed() {
+        assert_eq!("a", rp("a").reversed().as_str());
+        assert_eq!("c/b/a", rp("a/b/c").reversed().as_str());
+        assert_eq!("c/b/a", rp("a/b/c/").reversed().as_str());
+        assert_eq!("c/b/a", rp("//a///b/c//").reversed().as_str());
+        assert_eq!("../b/.", rp("./b/..").reversed().as_str());
+        assert_eq!("a/b/c", rp("a/b/c").reversed().reversed().as_str());
+    }
+
+    #[test]
+    fn test_resolved_len() {
+        let bases = ["", "base", "base/", "/", "/base//"];
+        let paths = [
+            "",
+            ".",
+            "..",
+            "../..",
+            "a/..",
+            "a/../..",
+            "./a/b/../c/",
+            "a//b",
+            "../a",
+        ];
+
+        for base in bases {
+            let base = Path::new(base);
+
+            for path in paths {
+                let path = rp(path);
+                let expected = path.normalize().to_path(base).as_os_str().len();
+                assert_eq!(
+                    expected,
+                    path.resolved_len(base),
+                    "{:?} on {:?}",
+                    path,
+                    base
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_extension() {
+        assert_eq!(
+            "foo/bar.md",
+            rp("foo/bar.txt").with_extension("md").as_str()
+        );
+        assert_eq!("foo/bar.md", rp("foo/bar").with_extension("md").as_str());
+        assert_eq!("foo/bar", rp("foo/bar.txt").with_extension("").as_str());
+        assert_eq!(
+            "foo//bar.md",
+            rp("foo//bar.txt").with_extension("md").as_str()
+        );
+    }
+
+    #[test]
+    fn test_starts_with_ends_with() {
+        let path = rp("foo///bar/baz");
+
+        assert!(path.starts_with("foo/bar"));
+        assert!(path.starts_with("/foo//bar/"));
+        assert!(path.starts_with(""));
+        assert!(!path.starts_with("fo"));
+        assert!(!path.starts_with("foo/bar/baz/biz"));
+
+        assert!(path.ends_with("bar/baz"));
+        assert!(path.ends_with("bar//baz/"));
+        assert!(path.ends_with(""));
+        assert!(!path.ends_with("az"));
+        assert!(!path.ends_with("biz/foo/bar/baz"));
+    }
+
+    #[test]
+    fn test_clone() {
+        let path = RelativePathBuf::from("foo///bar/./baz/");
+        let cloned = path.clone();
+
+        assert_eq!(path, cloned);
+        assert_eq!(path.as_str(), cloned.as_str());
+    }
+
+    #[test]
+    fn test_decoded_components() {
+        fn decode(path: &str) -> Result<Vec<Cow<'_, str>>, DecodeError> {
+            rp(path).decoded_components().collect()
+        }
+
+        assert_eq!(Ok(vec![]), decode(""));
+        assert_eq!(Ok(vec!["a".into(), "b".into()]), decode("a//b/"));
+        assert_eq!(Ok(vec!["%".into()]), decode("%25"));
+        assert_eq!(Ok(vec!["..".into()]), decode("%2E%2e"));
+        assert_eq!(Err(DecodeErrorKind::InvalidEscape.into()), decode("a%"));
+        assert_eq!(Err(DecodeErrorKind::InvalidEscape.into()), decode("a%2"));
+        assert_eq!(Err(DecodeErrorKind::InvalidEscape.into()), decode("a%g0"));
+        assert_eq!(
+            Err(DecodeErrorKind::EncodedSeparator.into()),
+            decode("a%2f")
+        );
+        assert_eq!(Err(DecodeErrorKind::NonUtf8.into()), decode("%C3"));
+        assert_eq!(Err(DecodeErrorKind::NonUtf8.into()), decode("%FF"));
+    }
+
+    #[test]
+    fn test_components_size_hint() {
+        for path in ["", "/", "a", "a/b", "a//b/", "/a/b/c", "./../a", "ab/cd"] {
+            let (lower, upper) = rp(path).components().size_hint();
+            let count = rp(path).components().count();
+            assert!(lower <= count, "{:?}: {} > {}", path, lower, count);
+            assert!(upper.is_some_and(|upper| count <= upper), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_boxed() {
+        let boxed: Box<RelativePath> = RelativePathBuf::from("foo//bar/").into();
+        let cloned = boxed.clone();
+        assert_eq!(boxed, cloned);
+        assert_eq!("foo//bar/", cloned.as_str());
+
+        let boxed: Box<RelativePath> = rp("").into();
+        assert_eq!("", RelativePathBuf::from(boxed).as_str());
+    }
+
+    #[test]
+    fn test_shared() {
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        let rc: Rc<RelativePath> = rp("foo//bar").into();
+        assert_eq!("foo//bar", rc.as_str());
+        let rc: Rc<RelativePath> = RelativePathBuf::from("foo/bar").into();
+        assert_eq!(rp("foo/bar"), &*rc);
+
+        let arc: Arc<RelativePath> = rp("foo//bar").into();
+        assert_eq!("foo//bar", arc.as_str());
+        let arc: Arc<RelativePath> = RelativePathBuf::from("foo/bar").into();
+        let other = Arc::clone(&arc);
+        assert!(std::thread::spawn(move || other.ends_with("bar"))
+            .join()
+            .unwrap());
+        assert_eq!(rp("foo/bar"), &*arc);
+    }
+
+    #[test]
+    fn test_components_as_relative_path() {
+        let mut it = rp("/a/./b//c/../d//").components();
+        assert_eq!("/a/./b//c/../d//", it.as_relative_path().as_str());
+        it.next_back();
+        assert_eq!("/a/./b//c/..", it.as_relative_path().as_str());
+        it.next();
+        assert_eq!("./b//c/..", it.as_relative_path().as_str());
+        it.next_back();
+        it.next_back();
+        assert_eq!("./b", it.as_relative_path().as_str());
+        it.next();
+        it.next();
+        assert_eq!("", it.as_relative_path().as_str());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.next_back());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_round_trip() {
+        let path = RelativePathBuf::from("foo//bar/./baz.txt");
+
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!("\"foo//bar/./baz.txt\"", json);
+        assert_eq!(
+            json,
+            serde_json::to_string(path.as_relative_path()).unwrap()
+        );
+
+        let back: RelativePathBuf = serde_json::from_str(&json).unwrap();
+        assert_eq!(path.as_str(), back.as_str());
+
+        assert!(serde_json::from_str::<RelativePathBuf>("42").is_err());
+
+        let boxed: Box<RelativePath> = serde_json::from_str(&json).unwrap();
+        assert_eq!(path.as_str(), boxed.as_str());
+
+        let borrowed: &RelativePath = serde_json::from_str(&json).unwrap();
+        assert_eq!(path.as_str(), borrowed.as_str());
+        assert_eq!(
+            json.as_ptr() as usize + 1,
+            borrowed.as_str().as_ptr() as usize
+        );
+
+        // Escapes can't be borrowed from the input.
+        assert!(serde_json::from_str::<&RelativePath>("\"foo\\/bar\"").is_err());
+    }
+
     #[test]
     fn test_from() {
         assert_eq!(
@@ -2022,6 +5456,8 @@ mod tests {
         tp!("foo/bar", "foo", true);
         tp!("foo/.", "", true);
         tp!("foo//bar", "foo", true);
+        tp!("foo/bar///", "foo", true);
+        tp!("foo///bar", "foo", true);
     }
 
     #[test]
@@ -2034,6 +5470,52 @@ mod tests {
         assert_eq!(format!("{}", RelativePath::new("foo/bar")), "foo/bar");
     }
 
+    #[test]
+    fn test_truncate_to_bytes() {
+        let mut p = RelativePathBuf::from("foo/bar/");
+        assert!(p.truncate_to_bytes(7));
+        assert_eq!("foo/bar", p.as_str());
+
+        let mut p = RelativePathBuf::from("foo/bar//");
+        assert!(p.truncate_to_bytes(8));
+        assert_eq!("foo/bar", p.as_str());
+
+        let mut p = RelativePathBuf::from("foo/bar/");
+        assert!(p.truncate_to_bytes(6));
+        assert_eq!("foo", p.as_str());
+
+        let mut p = RelativePathBuf::from("foo/bar/baz");
+        assert!(p.truncate_to_bytes(7));
+        assert_eq!("foo/bar", p.as_str());
+
+        let mut p = RelativePathBuf::from("foo/bar/baz");
+        assert!(p.truncate_to_bytes(3));
+        assert_eq!("foo", p.as_str());
+
+        let mut p = RelativePathBuf::from("foo/bar/baz");
+        assert!(!p.truncate_to_bytes(11));
+        assert_eq!("foo/bar/baz", p.as_str());
+    }
+
+    #[test]
+    fn test_has_control_chars() {
+        assert!(!rp("foo/bar.txt").has_control_chars());
+        assert!(!rp("foo/b\u{e4}r/\u{80}x").has_control_chars());
+        assert!(rp("a\u{7f}").has_control_chars());
+        assert!(rp("a\u{1f}/b").has_control_chars());
+        assert!(rp("a/\tb").has_control_chars());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_from_path_error_description() {
+        use std::error::Error as _;
+
+        let error = FromPathError::from(FromPathErrorKind::NonRelative);
+        assert_eq!("path contains non-relative component", error.description());
+        assert_eq!(error.to_string(), error.description());
+    }
+
     #[cfg(unix)]
     #[test]
     pub fn test_unix_from_path() {